@@ -0,0 +1,99 @@
+//! DuckDB extension ABI types
+//!
+//! This module models the ABI types understood by DuckDB's extension loader
+//! together with the rules each of them imposes on the DuckDB version field.
+
+use cargo_metadata::semver::Version;
+use clap::ValueEnum;
+use std::fmt;
+
+/// First C API version of DuckDB's stable extension API
+pub(super) const MIN_C_API_VERSION: Version = Version::new(1, 2, 0);
+
+/// Stable C API versions published by DuckDB, in ascending order
+///
+/// DuckDB only bumps the stable C API version when new functions are
+/// stabilized, so several DuckDB releases share the same C API version.
+const C_API_VERSIONS: [Version; 1] = [MIN_C_API_VERSION];
+
+/// Derives the stable C API version provided by a DuckDB release
+///
+/// Returns the newest C API version that is not newer than the release,
+/// or `None` for releases predating the stable C API.
+pub(super) fn c_api_version_for(duckdb_version: &Version) -> Option<Version> {
+    let release = Version::new(duckdb_version.major, duckdb_version.minor, 0);
+    C_API_VERSIONS.iter().rev().find(|version| *version <= &release).cloned()
+}
+
+/// ABI type recorded in the extension footer
+///
+/// The ABI type tells DuckDB how to initialize the extension and how to
+/// interpret the DuckDB version field of the footer.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum AbiType {
+    /// Stable C API; the version field holds the minimum C API version
+    #[value(name = "C_STRUCT")]
+    CStruct,
+    /// Unstable C API; the version field holds the exact DuckDB release
+    #[value(name = "C_STRUCT_UNSTABLE")]
+    CStructUnstable,
+    /// C++ API; the version field holds the exact DuckDB release
+    #[value(name = "CPP")]
+    Cpp,
+}
+
+impl AbiType {
+    /// Parses the ABI type stored in an existing footer
+    ///
+    /// Extensions packed before DuckDB introduced ABI types leave the field
+    /// empty, which DuckDB reads as `CPP`. Returns `None` for unknown values.
+    pub(super) fn from_footer(value: &str) -> Option<AbiType> {
+        match value {
+            "" => Some(AbiType::Cpp),
+            value => AbiType::value_variants().iter().find(|abi_type| abi_type.as_str() == value).copied(),
+        }
+    }
+
+    /// Returns the identifier written into the footer
+    pub(super) fn as_str(&self) -> &'static str {
+        match self {
+            AbiType::CStruct => "C_STRUCT",
+            AbiType::CStructUnstable => "C_STRUCT_UNSTABLE",
+            AbiType::Cpp => "CPP",
+        }
+    }
+
+    /// Checks that a parsed DuckDB version is meaningful for this ABI
+    ///
+    /// C_STRUCT extensions record the minimum C API version they require,
+    /// which is always a `vMAJOR.MINOR.0` release of at least v1.2.0. The
+    /// other ABI types record an exact DuckDB release and accept any version.
+    pub(super) fn accepts_duckdb_version(&self, version: &Version) -> bool {
+        match self {
+            AbiType::CStruct => {
+                version.patch == 0 && version.pre.is_empty() && version >= &MIN_C_API_VERSION
+            }
+            AbiType::CStructUnstable | AbiType::Cpp => true,
+        }
+    }
+}
+
+impl fmt::Display for AbiType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_footer_abi_types() {
+        assert_eq!(AbiType::from_footer("C_STRUCT"), Some(AbiType::CStruct));
+        assert_eq!(AbiType::from_footer("C_STRUCT_UNSTABLE"), Some(AbiType::CStructUnstable));
+        assert_eq!(AbiType::from_footer("CPP"), Some(AbiType::Cpp));
+        assert_eq!(AbiType::from_footer(""), Some(AbiType::Cpp));
+        assert_eq!(AbiType::from_footer("cpp"), None);
+    }
+}
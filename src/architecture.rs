@@ -0,0 +1,68 @@
+//! Verification of the architecture and OS of a dynamic library
+//!
+//! This module reads the object header of a library (ELF `e_machine` and
+//! OSABI, Mach-O cputype, PE machine, or the WebAssembly magic) and checks
+//! it against the DuckDB platform the library is being packed for.
+
+use crate::console;
+use crate::error::ToolsError;
+use crate::logger::QUITE;
+use crate::platform::Platform;
+use object::elf::ELFOSABI_FREEBSD;
+use object::Architecture;
+use object::BinaryFormat;
+use object::Object;
+
+/// Magic bytes at the start of every WebAssembly module
+pub(super) const WASM_MAGIC: &[u8] = b"\0asm";
+
+/// Detects the DuckDB operating system and architecture names of a library
+///
+/// Returns `(os, arch)` using the same spelling as DuckDB platform
+/// identifiers, e.g. `("linux", "amd64")`.
+fn detect(library_path: &str, data: &[u8]) -> Result<(String, String), ToolsError> {
+    if data.starts_with(WASM_MAGIC) {
+        return Ok(("wasm".to_owned(), "wasm32".to_owned()));
+    }
+    let file = object::File::parse(data).map_err(|error| ToolsError::UnreadableLibrary {
+        path: library_path.to_owned(),
+        reason: error.to_string(),
+    })?;
+    let os = match file.format() {
+        // Byte 7 of the ELF identification is the OSABI
+        BinaryFormat::Elf if data.get(7) == Some(&ELFOSABI_FREEBSD) => "freebsd",
+        BinaryFormat::Elf => "linux",
+        BinaryFormat::MachO => "osx",
+        BinaryFormat::Pe | BinaryFormat::Coff => "windows",
+        format => return Ok((format!("{format:?}").to_lowercase(), String::new())),
+    };
+    let arch = match file.architecture() {
+        Architecture::X86_64 => "amd64".to_owned(),
+        Architecture::Aarch64 => "arm64".to_owned(),
+        Architecture::I386 => "x86".to_owned(),
+        Architecture::Arm => "arm".to_owned(),
+        architecture => format!("{architecture:?}").to_lowercase(),
+    };
+    Ok((os.to_owned(), arch))
+}
+
+/// Confirms that the library was built for the declared DuckDB platform
+///
+/// The operating system must always match. The architecture is compared
+/// only for platforms that name one, i.e. not for WebAssembly platforms.
+pub(super) fn verify_architecture(library_path: &str, platform: &Platform) -> Result<(), ToolsError> {
+    let data = std::fs::read(library_path)?;
+    let (os, arch) = detect(library_path, &data)?;
+    let os_matches = os == platform.os();
+    let arch_matches = platform.arch().is_none_or(|expected| expected == arch);
+    if os_matches && arch_matches {
+        console!("   Verifying Architecture ({os}, {arch})");
+        Ok(())
+    } else {
+        Err(ToolsError::PlatformMismatch {
+            library: library_path.to_owned(),
+            detected: format!("{os}_{arch}"),
+            platform: platform.to_string(),
+        })
+    }
+}
@@ -3,10 +3,36 @@
 //! This module provides the `duckdb-ext-build` subcommand that combines
 //! compilation and packaging in a single operation with intelligent defaults.
 
+use crate::abi::c_api_version_for;
+use crate::abi::AbiType;
+use crate::abi::MIN_C_API_VERSION;
+use crate::architecture::verify_architecture;
+use crate::compression::Compression;
+use crate::config::matches;
+use crate::config::DuckdbConfig;
+use crate::config::Source;
+use crate::config::Sourced;
+use crate::console;
+use crate::entrypoint::Entrypoint;
 use crate::error::ToolsError;
+use crate::exports::extension_name as extension_name_of;
+use crate::exports::verify_entrypoint;
 use crate::fs::open_duplicate;
 use crate::logger::QUITE;
+use crate::logger::set_quiet;
 use crate::packer::Packer;
+use crate::platform::Platform;
+use crate::platform::PlatformParser;
+use crate::repository::repository_path;
+use crate::target::build_targets;
+use crate::target::target_directory;
+use crate::target::target_name;
+use crate::target::target_triple;
+use crate::template::check_template;
+use crate::template::is_template;
+use crate::template::render_template;
+use crate::validation::Validator;
+use cargo_metadata::camino::Utf8Path;
 use cargo_metadata::camino::Utf8PathBuf;
 use cargo_metadata::semver::Version;
 use cargo_metadata::Message;
@@ -15,17 +41,22 @@ use cargo_metadata::MetadataCommand;
 use cargo_metadata::Package;
 use cargo_metadata::PackageId;
 use cargo_metadata::TargetKind;
-use cargo_metadata::{Artifact, PackageName};
+use cargo_metadata::Artifact;
+use cargo_metadata::DependencyKind;
+use clap::builder::BoolishValueParser;
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use clap::CommandFactory;
+use clap::FromArgMatches;
 use clap::Parser;
 use std::collections::HashMap;
-use std::env::consts::ARCH;
-use std::env::consts::OS;
-use std::io::BufRead;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::io::BufReader;
+use std::io::IsTerminal;
 use std::process::Command;
 use std::process::Stdio;
 use std::str::FromStr;
-use target_lexicon::Architecture;
-use target_lexicon::OperatingSystem;
 use target_lexicon::Triple;
 
 /// Command line options for the `duckdb-ext-build` subcommand
@@ -42,52 +73,187 @@ use target_lexicon::Triple;
 )]
 pub(super) struct BuilderOptions {
     /// Path to Cargo.toml (defaults to current directory)
-    #[arg(short = 'm', long, value_name = "MANIFEST-PATH")]
+    #[arg(short = 'm', long, value_name = "MANIFEST-PATH", env = "DUCKDB_EXT_MANIFEST_PATH")]
     manifest_path: Option<String>,
 
-    /// Output extension file path (auto-detected if not specified)
-    #[arg(short = 'o', long, value_name = "EXTENSION-PATH")]
+    /// Output extension file path, may contain {name}, {version}, {duckdb_version} and {platform} (auto-detected if not specified)
+    #[arg(short = 'o', long, value_name = "EXTENSION-PATH", env = "DUCKDB_EXT_PATH")]
     extension_path: Option<String>,
 
+    /// Directory to write extensions to instead of next to the library
+    #[arg(long, value_name = "OUT-DIR", env = "DUCKDB_EXT_OUT_DIR")]
+    out_dir: Option<String>,
+
+    /// Extension repository to write extensions to, as <REPOSITORY>/<DUCKDB-RELEASE>/<PLATFORM>/<NAME>.duckdb_extension
+    #[arg(long, value_name = "REPOSITORY", env = "DUCKDB_EXT_REPOSITORY", conflicts_with_all = ["extension_path", "out_dir"])]
+    repository: Option<String>,
+
     /// Extension version (auto-detected from Cargo.toml if not specified)
-    #[arg(short = 'v', long, value_name = "EXTENSION-VERSION")]
+    #[arg(short = 'v', long, value_name = "EXTENSION-VERSION", env = "DUCKDB_EXT_VERSION")]
     extension_version: Option<String>,
 
     /// Target platform (auto-detected from build target if not specified)
-    #[arg(short = 'p', long, value_name = "DUCKDB-PLATFORM")]
-    duckdb_platform: Option<String>,
+    #[arg(short = 'p', long, value_name = "DUCKDB-PLATFORM", value_parser = PlatformParser, env = "DUCKDB_PLATFORM")]
+    duckdb_platform: Option<Platform>,
 
     /// DuckDB version (auto-detected from dependencies if not specified)
-    #[arg(short = 'd', long, value_name = "DUCKDB-VERSION")]
+    #[arg(short = 'd', long, value_name = "DUCKDB-VERSION", env = "DUCKDB_VERSION")]
     duckdb_version: Option<String>,
 
+    /// Minimum C API version for C_STRUCT extensions (derived from dependencies if not specified)
+    #[arg(short = 'c', long, value_name = "C-API-VERSION", env = "DUCKDB_C_API_VERSION")]
+    c_api_version: Option<String>,
+
+    /// DuckDB release naming the --repository directory (the DuckDB version, or the dependency for C_STRUCT, if not specified)
+    #[arg(long, value_name = "DUCKDB-RELEASE", env = "DUCKDB_RELEASE")]
+    duckdb_release: Option<String>,
+
     /// ABI type (defaults to "C_STRUCT_UNSTABLE")
-    #[arg(
-        short = 'a',
-        long,
-        value_name = "ABI-TYPE",
-        default_value = "C_STRUCT_UNSTABLE"
-    )]
-    abi_type: String,
+    #[arg(short = 'a', long, value_name = "ABI-TYPE", value_enum, env = "DUCKDB_ABI_TYPE")]
+    abi_type: Option<AbiType>,
+
+    /// Compress extensions, replacing the plain files unless --keep-uncompressed is given
+    #[arg(long, value_name = "FORMAT", value_enum, env = "DUCKDB_EXT_COMPRESS")]
+    compress: Option<Compression>,
+
+    /// Keep uncompressed extensions next to the compressed ones
+    #[arg(long, default_value_t = false, env = "DUCKDB_EXT_KEEP_UNCOMPRESSED", value_parser = BoolishValueParser::new())]
+    keep_uncompressed: bool,
+
+    /// Skip checking that the library exports the entry point DuckDB will call
+    #[arg(long, default_value_t = false, env = "DUCKDB_EXT_NO_VERIFY_SYMBOLS", value_parser = BoolishValueParser::new())]
+    no_verify_symbols: bool,
+
+    /// Pack even if the library was built for a different platform than declared
+    #[arg(short = 'f', long, default_value_t = false, env = "DUCKDB_EXT_FORCE", value_parser = BoolishValueParser::new())]
+    force: bool,
 
     /// Suppress console output
-    #[arg(short = 'q', long, default_value_t = false)]
+    #[arg(short = 'q', long, default_value_t = false, env = "DUCKDB_EXT_QUIET", value_parser = BoolishValueParser::new())]
     quiet: bool,
 
-    /// Additional arguments passed to `cargo build`
+    /// Package to build and pack (may be repeated; `*` matches any characters)
+    #[arg(long = "package", value_name = "SPEC")]
+    packages: Vec<String>,
+
+    /// Package to leave out of the build (may be repeated; `*` matches any characters)
+    #[arg(long, value_name = "SPEC")]
+    exclude: Vec<String>,
+
+    /// Only pack the cdylib targets with this library name (may be repeated)
+    #[arg(long = "lib-name", value_name = "LIB-NAME")]
+    lib_names: Vec<String>,
+
+    /// Additional arguments passed to `cargo build` (replace `DUCKDB_EXT_CARGO_ARGS` and `cargo-args` from Cargo.toml)
     #[arg(raw = true)]
     args: Vec<String>,
+
+    /// Environment variables that supplied option values, by option id
+    #[arg(skip)]
+    environment: HashMap<String, String>,
 }
 
+/// Environment variable holding whitespace-separated arguments for `cargo build`
+const CARGO_ARGS_ENV: &str = "DUCKDB_EXT_CARGO_ARGS";
+
 impl BuilderOptions {
+    /// Builds the options from parsed matches
+    ///
+    /// Unlike `parse_from`, this records which values were read from
+    /// environment variables, so that their source can be reported.
+    pub(super) fn from_matches(matches: &ArgMatches) -> Self {
+        let mut options = Self::from_arg_matches(matches).unwrap_or_else(|error| error.exit());
+        options.environment = Self::command()
+            .get_arguments()
+            .filter(|argument| matches.value_source(argument.get_id().as_str()) == Some(ValueSource::EnvVariable))
+            .filter_map(|argument| {
+                let name = argument.get_env()?.to_str()?;
+                Some((argument.get_id().to_string(), name.to_owned()))
+            })
+            .collect();
+        options
+    }
+
+    /// Attaches the source of an option to its value
+    fn sourced<T: Clone>(&self, id: &str, value: &Option<T>) -> Option<Sourced<T>> {
+        let source = match self.environment.get(id) {
+            Some(name) => Source::Environment(name.to_owned()),
+            None => Source::CommandLine,
+        };
+        value.to_owned().map(|value| Sourced::new(value, source))
+    }
+
+    /// Resolves the arguments passed to `cargo build`
+    ///
+    /// Arguments after `--` win over `DUCKDB_EXT_CARGO_ARGS`, which wins
+    /// over `cargo-args` in Cargo.toml.
+    fn cargo_args(&self, config: &DuckdbConfig) -> Sourced<Vec<String>> {
+        if !self.args.is_empty() {
+            return Sourced::new(self.args.to_owned(), Source::CommandLine);
+        }
+        if let Ok(args) = std::env::var(CARGO_ARGS_ENV)
+            && !args.trim().is_empty()
+        {
+            let args = args.split_whitespace().map(str::to_owned).collect();
+            return Sourced::new(args, Source::Environment(CARGO_ARGS_ENV.to_owned()));
+        }
+        config
+            .cargo_args
+            .to_owned()
+            .unwrap_or_else(|| Sourced::new(Vec::new(), Source::Default))
+    }
+
+    /// Applies `--package`, `--exclude` and `--lib-name` to the extension packages
+    ///
+    /// Fails if a `--package` or `--lib-name` value selects none of them.
+    fn select_packages<'a>(&self, candidates: Vec<&'a Package>) -> Result<Vec<&'a Package>, ToolsError> {
+        let unmatched = self.packages
+            .iter()
+            .find(|spec| !candidates.iter().any(|package| package_matches(spec, package)))
+            .map(|spec| ("--package", spec))
+            .or_else(|| self.lib_names
+                .iter()
+                .find(|name| !candidates.iter().any(|package| lib_matches(name, package)))
+                .map(|name| ("--lib-name", name)));
+        if let Some((option, value)) = unmatched {
+            return Err(ToolsError::NoMatchingPackage {
+                option,
+                value: value.to_owned(),
+            });
+        }
+        Ok(candidates
+            .into_iter()
+            .filter(|package| self.packages.is_empty() || self.packages.iter().any(|spec| package_matches(spec, package)))
+            .filter(|package| !self.exclude.iter().any(|spec| package_matches(spec, package)))
+            .filter(|package| self.lib_names.is_empty() || self.lib_names.iter().any(|name| lib_matches(name, package)))
+            .collect())
+    }
+
+    /// Returns whether packages were selected explicitly
+    fn selects_packages(&self) -> bool {
+        !self.packages.is_empty() || !self.exclude.is_empty() || !self.lib_names.is_empty()
+    }
+
     /// Creates a cargo build command with JSON message format
     ///
     /// This sets up the cargo command to produce JSON output that can be
-    /// parsed to extract build artifact information.
-    fn cargo(&self) -> Command {
+    /// parsed to extract build artifact information. Diagnostics are
+    /// pre-rendered by cargo, with colors when stderr is a terminal.
+    /// Explicitly selected packages are passed on with `--package`.
+    fn cargo(&self, cargo_args: &[String], packages: &[Package]) -> Command {
         let mut command = Command::new("cargo");
-        let mut args = vec!["build".to_string(), "--message-format=json".to_string()];
-        args.extend_from_slice(&self.args);
+        let message_format = if std::io::stderr().is_terminal() {
+            "--message-format=json-diagnostic-rendered-ansi"
+        } else {
+            "--message-format=json"
+        };
+        let mut args = vec!["build".to_string(), message_format.to_string()];
+        if self.selects_packages() {
+            for package in packages {
+                args.extend(["--package".to_string(), package.name.to_string()]);
+            }
+        }
+        args.extend_from_slice(cargo_args);
         command.args(&args);
         command.stdout(Stdio::piped());
         command
@@ -114,20 +280,36 @@ impl BuilderOptions {
 pub(super) struct Builder {
     /// Cargo build command with JSON output enabled
     command: Command,
+    /// Arguments passed to `cargo build`
+    cargo_args: Sourced<Vec<String>>,
     /// Target directory where build artifacts are located
     target_directory: Utf8PathBuf,
+    /// Targets cargo builds for, empty for native builds
+    targets: Vec<Sourced<String>>,
     /// Optional override for extension output path
-    extension_path: Option<String>,
+    extension_path: Option<Sourced<String>>,
+    /// Optional directory for extension files
+    out_dir: Option<Sourced<String>>,
+    /// Optional extension repository directory
+    repository: Option<Sourced<String>>,
+    /// Optional compression of extension files
+    compression: Option<Sourced<Compression>>,
+    /// Whether to keep plain files next to compressed ones
+    keep_uncompressed: bool,
     /// Optional override for extension version
-    extension_version: Option<String>,
+    extension_version: Option<Sourced<String>>,
     /// Optional override for target platform
-    duckdb_platform: Option<String>,
-    /// DuckDB version compatibility
-    duckdb_version: String,
-    /// ABI type specification
-    abi_type: String,
+    duckdb_platform: Option<Sourced<Platform>>,
     /// List of packages in the workspace that produce CDyLib targets
     packages: Vec<Package>,
+    /// Entrypoint attributes declared by the CDyLib target of each package
+    entrypoints: HashMap<PackageId, Entrypoint>,
+    /// Packaging settings of each package
+    settings: HashMap<PackageId, PackageSettings>,
+    /// Whether to check the exported entry point before packing
+    verify_symbols: bool,
+    /// Whether to pack libraries whose architecture or OS mismatch the platform
+    force: bool,
 }
 
 impl TryFrom<BuilderOptions> for Builder {
@@ -135,46 +317,287 @@ impl TryFrom<BuilderOptions> for Builder {
 
     /// Constructs a Builder from command line options
     ///
-    /// This conversion extracts project metadata, detects DuckDB version
-    /// from dependencies, and filters packages that produce dynamic libraries.
+    /// This conversion validates the user-provided metadata values, extracts
+    /// project metadata, detects DuckDB version from dependencies, filters
+    /// packages that produce dynamic libraries, and reads their entrypoint
+    /// attributes.
     fn try_from(parameters: BuilderOptions) -> Result<Self, Self::Error> {
-        QUITE.set(parameters.quiet).expect("Failed to set quiet");
+        set_quiet(parameters.quiet)?;
+        Validator::default()
+            .extension_version(parameters.extension_version.as_deref())
+            .duckdb_version(AbiType::CStruct, parameters.c_api_version.as_deref())
+            .duckdb_release(parameters.duckdb_release.as_deref())
+            .duckdb_platform(parameters.duckdb_platform.as_ref())
+            .finish()?;
+        if let Some(extension_path) = parameters.extension_path.as_ref() {
+            check_template(extension_path)?;
+        }
         let metadata = parameters.open_manifest()?;
-        let target_directory = metadata.target_directory;
-
-        // Auto-detect DuckDB version from dependencies
-        let duckdb_version = metadata
-            .packages
-            .iter()
-            .find_map(|package| {
-                if package.name == "duckdb" || package.name == "libduckdb-sys" {
-                    Some(format!("v{}", package.version))
-                } else {
-                    None
-                }
-            });
+        let workspace_manifest = metadata.workspace_root.join("Cargo.toml");
+        let workspace_config = DuckdbConfig::parse(
+            &metadata.workspace_metadata,
+            "workspace.metadata.duckdb",
+            &workspace_manifest,
+        )?;
+        let root_config = match metadata.root_package() {
+            Some(package) => DuckdbConfig::parse(&package.metadata, "package.metadata.duckdb", &package.manifest_path)?
+                .or(&workspace_config),
+            None => workspace_config.to_owned(),
+        };
+        let cargo_args = parameters.cargo_args(&root_config);
+        let target_directory = target_directory(&cargo_args.value)?.unwrap_or(metadata.target_directory.to_owned());
+        let targets = build_targets(&cargo_args.value)?;
 
         // Filter packages that are workspace members and produce CDyLib targets
-        let packages = metadata
+        let candidates = metadata
             .packages
-            .into_iter()
+            .iter()
             .filter(|package| metadata.workspace_members.contains(&package.id))
             .filter(|package| package.targets.iter().any(|target| target.kind.contains(&TargetKind::CDyLib)))
             .collect::<Vec<_>>();
+        let packages = parameters
+            .select_packages(candidates)?
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        let entrypoints = Self::read_entrypoints(&packages)?;
+        let mut settings = HashMap::new();
+        for package in &packages {
+            let config = DuckdbConfig::parse(&package.metadata, "package.metadata.duckdb", &package.manifest_path)?
+                .or(&workspace_config);
+            let entrypoint = entrypoints.get(&package.id);
+            let package_settings = PackageSettings::resolve(&parameters, config, package, entrypoint, &metadata)?;
+            settings.insert(package.id.to_owned(), package_settings);
+        }
 
-        Ok(Self {
-            command: parameters.cargo(),
+        let builder = Self {
+            command: parameters.cargo(&cargo_args.value, &packages),
+            cargo_args,
             target_directory,
-            extension_path: parameters.extension_path,
-            extension_version: parameters.extension_version,
-            duckdb_platform: parameters.duckdb_platform,
-            duckdb_version: parameters.duckdb_version.or(duckdb_version).expect("Missing duckdb version"),
-            abi_type: parameters.abi_type,
+            targets,
+            extension_path: parameters.sourced("extension_path", &parameters.extension_path),
+            out_dir: parameters.sourced("out_dir", &parameters.out_dir),
+            repository: parameters.sourced("repository", &parameters.repository),
+            compression: parameters.sourced("compress", &parameters.compress),
+            keep_uncompressed: parameters.keep_uncompressed,
+            extension_version: parameters.sourced("extension_version", &parameters.extension_version),
+            duckdb_platform: parameters.sourced("duckdb_platform", &parameters.duckdb_platform),
             packages,
+            entrypoints,
+            settings,
+            verify_symbols: !parameters.no_verify_symbols,
+            force: parameters.force,
+        };
+        builder.check_extension_paths()?;
+        for package in &builder.packages {
+            if let Some(entrypoint) = builder.entrypoints.get(&package.id) {
+                Self::check_entrypoint(&parameters, package, entrypoint, &builder.settings[&package.id])?;
+            }
+        }
+        Ok(builder)
+    }
+}
+
+/// Packaging settings of a package
+///
+/// Command line options and environment variables take precedence over the
+/// package's configuration, which is already merged over the workspace's.
+struct PackageSettings {
+    /// Merged `[package.metadata.duckdb]` and `[workspace.metadata.duckdb]`
+    config: DuckdbConfig,
+    /// ABI type specification
+    abi_type: Sourced<AbiType>,
+    /// DuckDB version recorded in the footer (the minimum C API version for C_STRUCT)
+    duckdb_version: Sourced<String>,
+    /// DuckDB release the extension is built for, which names its repository directory
+    duckdb_release: Option<Sourced<String>>,
+}
+
+impl PackageSettings {
+    /// Applies the command line over a package's configuration
+    ///
+    /// The DuckDB version is taken from the command line or configuration,
+    /// the entrypoint's `min_duckdb_version` (C_STRUCT only) or the
+    /// dependencies, and must be known before anything is built. The DuckDB
+    /// release naming the repository directory defaults to the dependency
+    /// for C_STRUCT and to the DuckDB version otherwise. Each value is
+    /// resolved first; the resolved values and the configured platforms are
+    /// then validated together.
+    fn resolve(
+        parameters: &BuilderOptions,
+        config: DuckdbConfig,
+        package: &Package,
+        entrypoint: Option<&Entrypoint>,
+        metadata: &Metadata,
+    ) -> Result<Self, ToolsError> {
+        let abi_type = parameters
+            .sourced("abi_type", &parameters.abi_type)
+            .or_else(|| config.abi_type.to_owned())
+            .unwrap_or_else(|| Sourced::new(AbiType::CStructUnstable, Source::Default));
+        let duckdb_version = parameters
+            .sourced("duckdb_version", &parameters.duckdb_version)
+            .or_else(|| config.duckdb_version.to_owned());
+        let c_api_version = parameters
+            .sourced("c_api_version", &parameters.c_api_version)
+            .or_else(|| config.c_api_version.to_owned());
+        let duckdb_release = parameters
+            .sourced("duckdb_release", &parameters.duckdb_release)
+            .or_else(|| config.duckdb_release.to_owned());
+        let min_duckdb_version = entrypoint
+            .filter(|_| abi_type.value == AbiType::CStruct)
+            .and_then(|entrypoint| entrypoint.min_duckdb_version.to_owned())
+            .map(|version| Sourced::new(version, Source::Entrypoint));
+        let dependency = DuckdbDependency::find(metadata, package)?;
+        let detected_duckdb_version = Builder::detect_duckdb_version(
+            abi_type.value,
+            duckdb_version.as_ref().map(|version| &version.value),
+            c_api_version.as_ref().map(|version| &version.value),
+            dependency.as_ref(),
+        );
+        let duckdb_version = match abi_type.value {
+            AbiType::CStruct => c_api_version.or(duckdb_version),
+            AbiType::CStructUnstable | AbiType::Cpp => duckdb_version,
+        }
+            .or(min_duckdb_version)
+            .or(detected_duckdb_version)
+            .ok_or_else(|| ToolsError::MissingDuckdbVersion(package.name.to_string()))?;
+
+        // Platforms given on the command line were already validated
+        config
+            .platforms()
+            .chain(config.duckdb_platform.as_ref())
+            .filter(|_| parameters.duckdb_platform.is_none())
+            .fold(Validator::default(), |validator, platform| validator.duckdb_platform(Some(&platform.value)))
+            .duckdb_version(abi_type.value, Some(&duckdb_version.value))
+            .duckdb_release(duckdb_release.as_ref().map(|release| release.value.as_str()))
+            .finish()?;
+
+        // The footer of a C_STRUCT extension records a C API version rather than the release
+        let duckdb_release = duckdb_release.or_else(|| match abi_type.value {
+            AbiType::CStruct => dependency.as_ref().map(DuckdbDependency::release),
+            AbiType::CStructUnstable | AbiType::Cpp => Some(duckdb_version.to_owned()),
+        });
+        Ok(Self {
+            config,
+            abi_type,
+            duckdb_version,
+            duckdb_release,
         })
     }
 }
 
+/// Effective packaging values of an extension, with their sources
+pub(super) struct ExtensionSettings {
+    /// Name DuckDB loads the extension by
+    pub(super) extension_name: Sourced<String>,
+    /// Output extension file path
+    pub(super) extension_path: Sourced<String>,
+    /// Extension version
+    pub(super) extension_version: Sourced<String>,
+    /// DuckDB platform
+    pub(super) duckdb_platform: Sourced<Platform>,
+    /// DuckDB version (the minimum C API version for C_STRUCT)
+    pub(super) duckdb_version: Sourced<String>,
+    /// ABI type
+    pub(super) abi_type: Sourced<AbiType>,
+    /// Compression applied to the extension file
+    pub(super) compression: Option<Sourced<Compression>>,
+}
+
+/// DuckDB crate found in the dependency graph
+struct DuckdbDependency<'a> {
+    /// The `duckdb` or `libduckdb-sys` package
+    package: &'a Package,
+    /// Features enabled on that package in the resolved graph
+    features: Vec<String>,
+}
+
+impl<'a> DuckdbDependency<'a> {
+    /// Returns the DuckDB release of the crate
+    fn release(&self) -> Sourced<String> {
+        Sourced::new(format!("v{}", self.package.version), self.source())
+    }
+
+    /// Describes the crate as the source of a derived value
+    fn source(&self) -> Source {
+        Source::Dependency(format!("{} v{}", self.package.name, self.package.version))
+    }
+
+    /// Finds the `duckdb` or `libduckdb-sys` package a package links against
+    ///
+    /// The resolved dependency graph is walked from the package along normal
+    /// dependencies, so that crates only used by other workspace members,
+    /// build scripts or tests are not picked up. The nearest DuckDB crate is
+    /// returned; all DuckDB crates reached must share its version.
+    fn find(metadata: &'a Metadata, package: &Package) -> Result<Option<Self>, ToolsError> {
+        let Some(resolve) = metadata.resolve.as_ref() else {
+            return Ok(None);
+        };
+        let nodes = resolve.nodes.iter().map(|node| (&node.id, node)).collect::<HashMap<_, _>>();
+        let mut visited = HashSet::from([&package.id]);
+        let mut queue = VecDeque::from([&package.id]);
+        let mut dependencies = Vec::new();
+        while let Some(id) = queue.pop_front() {
+            let Some(node) = nodes.get(id) else {
+                continue;
+            };
+            if id != &package.id
+                && let Some(dependency) = metadata.packages.iter().find(|dependency| &dependency.id == id)
+                && (dependency.name == "duckdb" || dependency.name == "libduckdb-sys")
+            {
+                let features = node.features.iter().map(|feature| feature.to_string()).collect();
+                dependencies.push(Self { package: dependency, features });
+            }
+            let normal = node.deps.iter().filter(|dependency| {
+                dependency.dep_kinds.is_empty()
+                    || dependency.dep_kinds.iter().any(|info| info.kind == DependencyKind::Normal)
+            });
+            for dependency in normal {
+                if visited.insert(&dependency.pkg) {
+                    queue.push_back(&dependency.pkg);
+                }
+            }
+        }
+
+        let Some(nearest) = dependencies.first() else {
+            return Ok(None);
+        };
+        if dependencies.iter().any(|dependency| dependency.package.version != nearest.package.version) {
+            return Err(ToolsError::ConflictingDuckdbVersions {
+                package: package.name.to_string(),
+                candidates: dependencies
+                    .iter()
+                    .map(|dependency| format!("{} v{}", dependency.package.name, dependency.package.version))
+                    .collect(),
+            });
+        }
+        Ok(dependencies.into_iter().next())
+    }
+}
+
+/// Matches a package against a `--package` or `--exclude` spec
+///
+/// The spec is a package name, optionally followed by `@<version>`.
+fn package_matches(spec: &str, package: &Package) -> bool {
+    let (name, version) = match spec.split_once('@') {
+        Some((name, version)) => (name, Some(version)),
+        None => (spec, None),
+    };
+    matches(name, &package.name)
+        && version.is_none_or(|version| version.strip_prefix('v').unwrap_or(version) == package.version.to_string())
+}
+
+/// Matches the cdylib target of a package against a `--lib-name` value
+///
+/// Dashes and underscores are equivalent, as in cargo's library names.
+fn lib_matches(name: &str, package: &Package) -> bool {
+    package
+        .targets
+        .iter()
+        .filter(|target| target.kind.contains(&TargetKind::CDyLib))
+        .any(|target| target.name.replace('-', "_") == name.replace('-', "_"))
+}
+
 /// Normalizes Windows paths by stripping extended-length device path prefix
 ///
 /// On Windows, canonicalize_utf8() returns paths with \\?\ prefix for extended-length
@@ -182,22 +605,145 @@ impl TryFrom<BuilderOptions> for Builder {
 /// are within the target directory.
 #[inline]
 fn normalize_windows_path(path: &str) -> String {
-    if cfg!(windows) {
-        if let Some(stripped) = path.strip_prefix("\\\\?\\") {
-            return stripped.to_owned();
-        }
+    if cfg!(windows) && let Some(stripped) = path.strip_prefix("\\\\?\\") {
+        return stripped.to_owned();
     }
     path.to_owned()
 }
 
 impl Builder {
+    /// Derives the footer's DuckDB version field from the package's dependencies
+    ///
+    /// The meaning of the field depends on the ABI type:
+    /// - C_STRUCT: the minimum C API version provided by the DuckDB crate
+    /// - C_STRUCT_UNSTABLE and CPP: the exact DuckDB crate version
+    ///
+    /// Inconsistent combinations of ABI type and the versions given on the
+    /// command line are reported as warnings.
+    fn detect_duckdb_version(
+        abi_type: AbiType,
+        duckdb_version: Option<&String>,
+        c_api_version: Option<&String>,
+        dependency: Option<&DuckdbDependency>,
+    ) -> Option<Sourced<String>> {
+        let dependency_version = dependency.map(|dependency| &dependency.package.version);
+        match abi_type {
+            AbiType::CStruct => {
+                if let Some(dependency) = dependency
+                    && !dependency.features.iter().any(|feature| feature == "loadable-extension")
+                {
+                    console!("     Warning {} is used without the `loadable-extension` feature required by C_STRUCT extensions", dependency.package.name);
+                }
+                let derived = dependency_version.and_then(c_api_version_for);
+                if let Some(version) = dependency_version
+                    && derived.is_none()
+                {
+                    console!("     Warning DuckDB v{version} predates the stable C API; C_STRUCT extensions need DuckDB v{MIN_C_API_VERSION} or later");
+                }
+                let explicit = c_api_version.or(duckdb_version);
+                if let Some(explicit) = explicit
+                    && let Some(derived) = derived.as_ref()
+                    && let Some(version) = explicit.strip_prefix('v').and_then(|version| Version::parse(version).ok())
+                    && &version > derived
+                {
+                    console!("     Warning C API version {explicit} is newer than v{derived} provided by the DuckDB dependency");
+                }
+                let dependency = dependency?;
+                derived.map(|version| Sourced::new(format!("v{version}"), dependency.source()))
+            }
+            AbiType::CStructUnstable | AbiType::Cpp => {
+                if c_api_version.is_some() {
+                    console!("     Warning The C API version is ignored for {abi_type} extensions, which record the exact DuckDB release");
+                }
+                if let Some(explicit) = duckdb_version
+                    && let Some(version) = dependency_version
+                    && explicit.strip_prefix('v') != Some(version.to_string().as_str())
+                {
+                    console!("     Warning {abi_type} extensions only load into the exact DuckDB release, but {explicit} differs from the DuckDB dependency v{version}");
+                }
+                dependency.map(DuckdbDependency::release)
+            }
+        }
+    }
+
+    /// Reads the `#[duckdb_entrypoint_c_api]` attribute of each package
+    ///
+    /// The attribute is looked up in the sources of the package's CDyLib
+    /// target; packages without the attribute are omitted.
+    fn read_entrypoints(packages: &[Package]) -> Result<HashMap<PackageId, Entrypoint>, ToolsError> {
+        let mut entrypoints = HashMap::new();
+        for package in packages {
+            let Some(target) = package.targets.iter().find(|target| target.kind.contains(&TargetKind::CDyLib)) else {
+                continue;
+            };
+            if let Some(entrypoint) = Entrypoint::read(&target.src_path)? {
+                entrypoints.insert(package.id.to_owned(), entrypoint);
+            }
+        }
+        Ok(entrypoints)
+    }
+
+    /// Ensures overrides agree with the entrypoint attributes of a package
+    ///
+    /// DuckDB derives the init symbol from the extension file name, and a
+    /// C_STRUCT extension checks the C API version it was initialized with,
+    /// so overriding either with a different value produces an extension
+    /// that fails to load. Overrides may come from the command line or from
+    /// the package's configuration.
+    fn check_entrypoint(
+        parameters: &BuilderOptions,
+        package: &Package,
+        entrypoint: &Entrypoint,
+        settings: &PackageSettings,
+    ) -> Result<(), ToolsError> {
+        let extension_path = parameters
+            .extension_path
+            .as_ref()
+            .or(settings.config.extension_path.as_ref().map(|path| &path.value));
+        let extension_names = [
+            extension_path
+                .filter(|extension_path| !is_template(extension_path))
+                .map(|extension_path| (Utf8Path::new(extension_path).file_stem(), extension_path)),
+            settings.config.extension_name.as_ref().map(|name| (Some(name.value.as_str()), &name.value)),
+        ];
+        if let Some(ext_name) = entrypoint.ext_name.as_ref()
+            && let Some((_, given)) = extension_names
+                .into_iter()
+                .flatten()
+                .find(|(extension_name, _)| *extension_name != Some(ext_name.as_str()))
+        {
+            return Err(ToolsError::EntrypointMismatch {
+                package: package.name.to_string(),
+                attribute: "ext_name",
+                declared: ext_name.to_owned(),
+                given: given.to_owned(),
+            });
+        }
+        if settings.abi_type.value == AbiType::CStruct
+            && let Some(min_duckdb_version) = entrypoint.min_duckdb_version.as_ref()
+            && &settings.duckdb_version.value != min_duckdb_version
+        {
+            return Err(ToolsError::EntrypointMismatch {
+                package: package.name.to_string(),
+                attribute: "min_duckdb_version",
+                declared: min_duckdb_version.to_owned(),
+                given: settings.duckdb_version.value.to_owned(),
+            });
+        }
+        Ok(())
+    }
+
     /// Executes the build process and creates Packer instances for each artifact
     ///
     /// This method:
     /// 1. Runs cargo build with JSON output
-    /// 2. Parses the JSON messages to find CDyLib artifacts
+    /// 2. Streams the JSON messages as they arrive, printing compiler
+    ///    diagnostics and collecting CDyLib artifacts
     /// 3. Matches artifacts with their corresponding packages
     /// 4. Creates Packer instances for packaging each extension
+    ///
+    /// Cargo's own progress output is written to the inherited stderr.
+    /// Fails if cargo exits unsuccessfully or no extension can be packaged.
     pub(super) fn build(&mut self) -> Result<Vec<Packer>, ToolsError> {
         let packages = self
             .packages
@@ -205,26 +751,53 @@ impl Builder {
             .map(|package| (package.id.to_owned(), package))
             .collect::<HashMap<PackageId, &Package>>();
 
-        // Execute cargo build and process JSON output
-        self
-            .command
-            .spawn()?
-            .wait_with_output()?
-            .stdout
-            .lines()
-            .filter_map(Result::ok)
-            .map(|json| serde_json::from_str::<Message>(&json))
-            .filter_map(Result::ok)
-            .filter_map(|message| match message {
-                Message::CompilerArtifact(artifact) if artifact.target.kind.contains(&TargetKind::CDyLib) => Some(artifact),
-                _ => None,
-            })
+        // Execute cargo build and process JSON output while it runs
+        let mut child = self.command.spawn()?;
+        let stdout = child.stdout.take().expect("cargo stdout is piped");
+        let mut artifacts = Vec::new();
+        let mut failure = None;
+        for message in Message::parse_stream(BufReader::new(stdout)) {
+            match message {
+                Ok(Message::CompilerMessage(message)) => {
+                    if let Some(rendered) = message.message.rendered {
+                        eprint!("{rendered}");
+                    }
+                }
+                Ok(Message::CompilerArtifact(artifact)) if artifact.target.kind.contains(&TargetKind::CDyLib) => {
+                    artifacts.push(artifact);
+                }
+                Ok(Message::TextLine(line)) => console!("{line}"),
+                Ok(_) => {}
+                Err(error) => {
+                    failure = Some(error);
+                    break;
+                }
+            }
+        }
+        if let Some(error) = failure {
+            // Cargo can no longer be read from, so stop it before reaping it
+            let _ = child.kill();
+            child.wait()?;
+            return Err(error.into());
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(ToolsError::BuildFailed(status.code()));
+        }
+
+        let packers = artifacts
+            .into_iter()
             .filter_map(|artifact| packages
                 .get(&artifact.package_id)
                 .map(|package| (package, artifact))
             )
             .flat_map(|(package, artifact)| self.packs(package, &artifact))
-            .collect::<Result<Vec<_>, _>>()
+            .collect::<Result<Vec<_>, _>>()?;
+        if packers.is_empty() {
+            let names = self.packages.iter().map(|package| package.name.to_string()).collect();
+            return Err(ToolsError::NoArtifacts(names));
+        }
+        Ok(packers)
     }
 
     /// Creates Packer instances for all filenames in an artifact
@@ -251,96 +824,262 @@ impl Builder {
                             .flatten()
                     })
             })
-            .map(|filename| self.pack(&package.name, &package.version, &filename))
+            .map(|filename| self.pack(package, &filename))
             .collect()
     }
 
     /// Creates a Packer instance for a specific library file
     ///
+    /// The effective values are resolved by `extension_settings`. They, the
+    /// exported entry point and the architecture of the library are validated
+    /// before the library is copied.
+    fn pack(&self, package: &Package, filename: &Utf8PathBuf) -> Result<Packer, ToolsError> {
+        let library_path = filename.to_string();
+        let target = self.artifact_target(filename)?;
+        let ExtensionSettings {
+            extension_name,
+            extension_path,
+            extension_version,
+            duckdb_platform,
+            duckdb_version,
+            abi_type,
+            compression,
+        } = self.extension_settings(package, &target, Some(filename))?;
+        // Explicit values were already validated in `try_from`
+        let derived_platform = matches!(duckdb_platform.source, Source::Target(_) | Source::Host(_));
+        Validator::default()
+            .extension_version((extension_version.source == Source::PackageVersion).then_some(&extension_version.value))
+            .duckdb_version(abi_type.value, Some(&duckdb_version.value))
+            .duckdb_platform(derived_platform.then_some(&duckdb_platform.value))
+            .finish()?;
+        if self.verify_symbols {
+            verify_entrypoint(&library_path, &extension_name.value, abi_type.value)?;
+        }
+        if !self.force {
+            verify_architecture(&library_path, &duckdb_platform.value)?;
+        }
+
+        let file = open_duplicate(&library_path, &extension_path.value)?;
+        Ok(Packer {
+            file,
+            extension_path: extension_path.value,
+            extension_version: extension_version.value,
+            duckdb_platform: duckdb_platform.value,
+            duckdb_version: duckdb_version.value,
+            abi_type: abi_type.value,
+            compression: compression.map(|compression| compression.value),
+            keep_uncompressed: self.keep_uncompressed,
+        })
+    }
+
+    /// Resolves the effective packaging values of a package for a build target
+    ///
     /// This method applies intelligent defaults for all parameters:
-    /// - Extension path: auto-generated from package name
+    /// - Extension path: the file in the extension repository, the rendered
+    ///   `-o`/`extension-path` template, or a file next to the library or in
+    ///   the output directory, named after the
+    ///   configured extension name, the entrypoint's `ext_name` or the package name
     /// - Extension version: extracted from Cargo.toml
-    /// - Platform: detected from build target or host system
-    /// - DuckDB version: from dependencies or user override
-    fn pack(&self, package_name: &PackageName, package_version: &Version, filename: &Utf8PathBuf) -> Result<Packer, ToolsError> {
-        let library_path = filename.to_string();
-        let extension_path = self.extension_path
+    /// - Platform: configured, or mapped from the build target or host system
+    /// - DuckDB version: from user override, the entrypoint's `min_duckdb_version`
+    ///   (C_STRUCT only), or dependencies
+    ///
+    /// Without a library, the default extension path names its directory
+    /// `<library directory>`.
+    pub(super) fn extension_settings(
+        &self,
+        package: &Package,
+        target: &Sourced<String>,
+        library: Option<&Utf8PathBuf>,
+    ) -> Result<ExtensionSettings, ToolsError> {
+        let entrypoint = self.entrypoints.get(&package.id);
+        let settings = &self.settings[&package.id];
+        let extension_name = settings.config.extension_name
             .to_owned()
-            .unwrap_or_else(|| self.artifact_extension_path(filename, package_name));
+            .or_else(|| entrypoint
+                .and_then(|entrypoint| entrypoint.ext_name.to_owned())
+                .map(|ext_name| Sourced::new(ext_name, Source::Entrypoint)))
+            .unwrap_or_else(|| Sourced::new(package.name.to_string(), Source::Default));
         let extension_version = self.extension_version
             .to_owned()
-            .unwrap_or_else(|| format!("v{package_version}"));
-        let duckdb_platform = self.duckdb_platform
-            .to_owned()
-            .or_else(|| self.artifact_duckdb_platform(filename))
-            .unwrap_or_else(Self::default_duckdb_platform);
-        let duckdb_version = self.duckdb_version.to_owned();
-        let abi_type = self.abi_type.to_owned();
+            .unwrap_or_else(|| Sourced::new(format!("v{}", package.version), Source::PackageVersion));
+        let duckdb_platform = match self.duckdb_platform.to_owned().or_else(|| settings.config.duckdb_platform.to_owned()) {
+            Some(duckdb_platform) => duckdb_platform,
+            None => self.target_duckdb_platform(settings, target)?,
+        };
+        let duckdb_version = settings.duckdb_version.to_owned();
 
-        let file = open_duplicate(&library_path, &extension_path)?;
-        Ok(Packer {
-            file,
+        let file_name = extension_name.value.replace('-', "_");
+        let out_dir = self.out_dir.as_ref().or(settings.config.out_dir.as_ref());
+        let repository = self.repository.as_ref().or(settings.config.repository.as_ref());
+        let extension_path = match (repository, self.extension_path.as_ref().or(settings.config.extension_path.as_ref())) {
+            (Some(repository), _) => {
+                let duckdb_release = settings.duckdb_release.as_ref().ok_or_else(|| ToolsError::MissingDuckdbRelease {
+                    extension: package.name.to_string(),
+                    hint: "pass it with --duckdb-release",
+                })?;
+                Sourced::new(
+                    repository_path(&repository.value, &duckdb_release.value, &duckdb_platform.value, &file_name),
+                    repository.source.to_owned(),
+                )
+            }
+            (None, Some(path)) => {
+                let rendered = render_template(&path.value, |placeholder| match placeholder {
+                    "name" => Some(&file_name),
+                    "version" => Some(&extension_version.value),
+                    "duckdb_version" => Some(&duckdb_version.value),
+                    "platform" => Some(duckdb_platform.value.as_str()),
+                    _ => None,
+                })?;
+                let rendered = match (out_dir, &path.source) {
+                    (Some(out_dir), _) => Utf8Path::new(&out_dir.value).join(rendered).to_string(),
+                    (None, Source::Config { manifest, .. }) => manifest
+                        .parent()
+                        .unwrap_or(Utf8Path::new(""))
+                        .join(rendered)
+                        .to_string(),
+                    (None, _) => rendered,
+                };
+                Sourced::new(rendered, path.source.to_owned())
+            }
+            (None, None) => match (out_dir, library) {
+                (Some(out_dir), _) => Sourced::new(
+                    Utf8Path::new(&out_dir.value).join(format!("{file_name}.duckdb_extension")).to_string(),
+                    out_dir.source.to_owned(),
+                ),
+                (None, Some(library)) => extension_name.to_owned().map(|name| self.artifact_extension_path(library, &name)),
+                (None, None) => Sourced::new(format!("<library directory>/{file_name}.duckdb_extension"), extension_name.source.to_owned()),
+            },
+        };
+        // DuckDB loads the extension by the file name, which only an explicit path overrides
+        let extension_name = match (repository, self.extension_path.as_ref().or(settings.config.extension_path.as_ref())) {
+            (None, Some(path)) if !is_template(&path.value) => {
+                Sourced::new(extension_name_of(&extension_path.value), extension_path.source.to_owned())
+            }
+            _ => extension_name.map(|_| file_name),
+        };
+        Ok(ExtensionSettings {
+            extension_name,
+            extension_path,
             extension_version,
             duckdb_platform,
             duckdb_version,
-            abi_type,
+            abi_type: settings.abi_type.to_owned(),
+            compression: self.compression.to_owned().or_else(|| settings.config.compression.to_owned()),
         })
     }
 
-    /// Generates the extension file path from the library path and package name
+    /// Rejects extension paths that several extensions would be written to
     ///
-    /// This replaces the library filename with the package name and changes
+    /// Paths given with `-o` or `extension-path` and default file names in
+    /// an output directory are resolved for every package and build target
+    /// before anything is built; templates must tell them apart.
+    fn check_extension_paths(&self) -> Result<(), ToolsError> {
+        let mut counts = HashMap::<String, (usize, Source)>::new();
+        for package in &self.packages {
+            let config = &self.settings[&package.id].config;
+            let fixed = [
+                &self.extension_path,
+                &config.extension_path,
+                &self.out_dir,
+                &config.out_dir,
+                &self.repository,
+                &config.repository,
+            ];
+            if fixed.iter().all(|path| path.is_none()) {
+                continue;
+            }
+            for target in self.build_targets() {
+                let path = self.extension_settings(package, &target, None)?.extension_path;
+                counts.entry(path.value).or_insert((0, path.source)).0 += 1;
+            }
+        }
+        match counts.into_iter().find(|(_, (count, _))| *count > 1) {
+            Some((path, (count, source))) => Err(ToolsError::ExtensionPathConflict {
+                path,
+                origin: source.to_string(),
+                count,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Lists the packages that produce CDyLib targets
+    pub(super) fn packages(&self) -> &[Package] {
+        &self.packages
+    }
+
+    /// Returns the arguments passed to `cargo build`
+    pub(super) fn cargo_args(&self) -> &Sourced<Vec<String>> {
+        &self.cargo_args
+    }
+
+    /// Lists the targets cargo builds for, or the host for native builds
+    pub(super) fn build_targets(&self) -> Vec<Sourced<String>> {
+        match self.targets.is_empty() {
+            true => vec![Self::host_target()],
+            false => self.targets.to_owned(),
+        }
+    }
+
+    /// Generates the extension file path from the library path and extension name
+    ///
+    /// This replaces the library filename with the extension name and changes
     /// the extension to `.duckdb_extension`.
-    fn artifact_extension_path(&self, filename: &Utf8PathBuf, package_name: &str) -> String {
+    fn artifact_extension_path(&self, filename: &Utf8PathBuf, extension_name: &str) -> String {
         let mut path = filename.clone();
-        path.set_file_name(package_name.replace('-', "_"));
+        path.set_file_name(extension_name.replace('-', "_"));
         path.set_extension("duckdb_extension");
         path.to_string()
     }
 
-    /// Extracts DuckDB platform identifier from the build artifact path
+    /// Determines the build target of an artifact
     ///
-    /// This method analyzes the target triple from the build directory
-    /// structure and maps it to DuckDB platform identifiers.
-    fn artifact_duckdb_platform(&self, filename: &Utf8PathBuf) -> Option<String> {
-        filename.strip_prefix(&self.target_directory)
-            .ok()
-            .and_then(|path| path.components().next())
-            .map(|target| Triple::from_str(target.as_str()))
-            .and_then(Result::ok)
-            .map(|triple| {
-                let os = match triple.operating_system {
-                    OperatingSystem::Linux => "linux",
-                    OperatingSystem::MacOSX(_) => "osx",
-                    OperatingSystem::Windows => "windows",
-                    _ => panic!("Unsupported operating system: {}", triple),
-                };
-                let arch = match triple.architecture {
-                    Architecture::X86_64 => "amd64",
-                    Architecture::Aarch64(_) => "arm64",
-                    Architecture::X86_32(_) => "amd",
-                    Architecture::Arm(_) => "arm",
-                    _ => panic!("Unsupported architecture: {}", triple),
-                };
-                format!("{os}_{arch}")
-            })
+    /// When several targets are built at once, the artifact is matched to its
+    /// target by the first path component below the target directory.
+    /// Native builds yield the host triple.
+    fn artifact_target(&self, filename: &Utf8PathBuf) -> Result<Sourced<String>, ToolsError> {
+        let target = match self.targets.as_slice() {
+            [] => return Ok(Self::host_target()),
+            [target] => target,
+            targets => {
+                let component = filename.strip_prefix(&self.target_directory)
+                    .ok()
+                    .and_then(|path| path.components().next());
+                targets.iter()
+                    .find(|target| component.is_some_and(|component| component.as_str() == target_name(&target.value)))
+                    .ok_or_else(|| ToolsError::AmbiguousTarget {
+                        library: filename.to_string(),
+                        targets: targets.iter().map(|target| target.value.as_str()).collect::<Vec<_>>().join(", "),
+                    })?
+            }
+        };
+        Ok(target.to_owned())
+    }
+
+    /// Returns the host triple as the target of native builds
+    fn host_target() -> Sourced<String> {
+        let host = Triple::host().to_string();
+        Sourced::new(host.to_owned(), Source::Host(host))
     }
 
-    /// Provides a default platform identifier based on the host system
+    /// Maps a build target to its DuckDB platform
     ///
-    /// This is used when no target triple is detected in the build path,
-    /// typically for native builds without explicit target specification.
-    fn default_duckdb_platform() -> String {
-        let os = match OS {
-            "macos" => "osx",
-            os => os,
-        };
-        let arch = match ARCH {
-            "x86" => "amd",
-            "x86_64" => "amd64",
-            "aarch64" => "arm64",
-            arch => arch,
+    /// The package's `platforms` configuration is consulted before the
+    /// built-in table.
+    fn target_duckdb_platform(&self, settings: &PackageSettings, target: &Sourced<String>) -> Result<Sourced<Platform>, ToolsError> {
+        if let Some(platform) = settings.config.platform(target_name(&target.value)) {
+            return Ok(platform.to_owned());
+        }
+        let triple = target_triple(&target.value)?;
+        let triple = Triple::from_str(&triple).map_err(|_| ToolsError::UnsupportedTarget {
+            triple,
+            component: "target triple",
+        })?;
+        let source = match &target.source {
+            Source::Host(_) => target.source.to_owned(),
+            _ => Source::Target(target.value.to_owned()),
         };
-        format!("{os}_{arch}")
+        Ok(Sourced::new(Platform::from_triple(&triple)?, source))
     }
 }
@@ -0,0 +1,72 @@
+//! Compression of extension files
+//!
+//! DuckDB downloads `<name>.duckdb_extension.gz` from remote repositories.
+//! The gzip header written here carries no modification time and no file
+//! name, so that compressing the same extension twice yields identical bytes.
+
+use clap::ValueEnum;
+use flate2::GzBuilder;
+use std::fmt;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+
+/// Compression formats for extension files
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Compression {
+    /// Gzip, as fetched by DuckDB from HTTP repositories
+    Gzip,
+}
+
+impl Compression {
+    /// Returns the file extension appended to compressed files
+    pub(super) fn extension(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz",
+        }
+    }
+
+    /// Compresses everything read from `reader` into `writer`
+    pub(super) fn compress(&self, mut reader: impl Read, writer: impl Write) -> io::Result<()> {
+        match self {
+            Compression::Gzip => {
+                let mut encoder = GzBuilder::new().mtime(0).write(writer, flate2::Compression::best());
+                io::copy(&mut reader, &mut encoder)?;
+                encoder.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.to_possible_value().expect("no skipped variants").get_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gzip(input: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        Compression::Gzip.compress(input, &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn gzip_is_deterministic() {
+        let input = b"quack".repeat(1000);
+        let compressed = gzip(&input);
+        assert_eq!(compressed, gzip(&input));
+
+        assert_eq!(compressed[..3], [0x1f, 0x8b, 0x08]);
+        assert_eq!(compressed[3] & 0x08, 0, "FNAME flag is set");
+        assert_eq!(compressed[4..8], [0; 4], "MTIME is set");
+
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(compressed.as_slice()).read_to_end(&mut decompressed).unwrap();
+        assert_eq!(decompressed, input);
+    }
+}
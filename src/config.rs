@@ -0,0 +1,327 @@
+//! Packaging configuration from `Cargo.toml` metadata
+//!
+//! Projects can tune packaging through a `duckdb` table in
+//! `[workspace.metadata]` and `[package.metadata]`, so that CI jobs do not
+//! have to repeat every option on the command line. Package values take
+//! precedence over workspace values, and command line options and their
+//! environment variables over both. Every effective value keeps its
+//! [`Source`], which `duckdb-ext-config show` reports.
+
+use crate::abi::AbiType;
+use crate::compression::Compression;
+use crate::error::ToolsError;
+use crate::platform::Platform;
+use crate::template::check_template;
+use cargo_metadata::camino::Utf8Path;
+use cargo_metadata::camino::Utf8PathBuf;
+use clap::ValueEnum;
+use serde_json::Map;
+use serde_json::Value;
+use std::fmt;
+
+/// Where an effective setting came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Source {
+    /// Given on the command line
+    CommandLine,
+    /// Read from the named environment variable
+    Environment(String),
+    /// Read from a key of a `[*.metadata.duckdb]` table
+    Config {
+        key: String,
+        section: &'static str,
+        manifest: Utf8PathBuf,
+    },
+    /// Declared by the `#[duckdb_entrypoint_c_api]` attribute
+    Entrypoint,
+    /// Taken from the package version in Cargo.toml
+    PackageVersion,
+    /// Derived from the named DuckDB dependency
+    Dependency(String),
+    /// Mapped from the build target
+    Target(String),
+    /// Given as the named option in the arguments passed to cargo
+    CargoOption(String),
+    /// Read from `build.target` in the named cargo configuration file
+    CargoConfig(String),
+    /// Mapped from the host the tool runs on
+    Host(String),
+    /// Built-in default
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::CommandLine => f.write_str("command line"),
+            Source::Environment(name) => write!(f, "environment variable {name}"),
+            Source::Config { key, section, manifest } => write!(f, "`{key}` in [{section}] of {manifest}"),
+            Source::Entrypoint => f.write_str("#[duckdb_entrypoint_c_api]"),
+            Source::PackageVersion => f.write_str("package version"),
+            Source::Dependency(dependency) => write!(f, "{dependency} dependency"),
+            Source::Target(target) => write!(f, "build target {target}"),
+            Source::CargoOption(option) => write!(f, "cargo option {option}"),
+            Source::CargoConfig(path) => write!(f, "`build.target` in {path}"),
+            Source::Host(triple) => write!(f, "host {triple}"),
+            Source::Default => f.write_str("default"),
+        }
+    }
+}
+
+/// A setting together with the place it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Sourced<T> {
+    pub(super) value: T,
+    pub(super) source: Source,
+}
+
+impl<T> Sourced<T> {
+    /// Attaches a source to a value
+    pub(super) fn new(value: T, source: Source) -> Self {
+        Self { value, source }
+    }
+
+    /// Transforms the value while keeping its source
+    pub(super) fn map<U>(self, transform: impl FnOnce(T) -> U) -> Sourced<U> {
+        Sourced::new(transform(self.value), self.source)
+    }
+}
+
+/// Settings read from a `[*.metadata.duckdb]` table
+#[derive(Debug, Default, Clone)]
+pub(super) struct DuckdbConfig {
+    /// `extension-name`: the name DuckDB loads the extension by
+    pub(super) extension_name: Option<Sourced<String>>,
+    /// `extension-path`: output extension file path or template, relative to the
+    /// output directory if there is one, else to the manifest
+    pub(super) extension_path: Option<Sourced<String>>,
+    /// `out-dir`: directory extensions are written to, relative to the manifest
+    pub(super) out_dir: Option<Sourced<String>>,
+    /// `repository`: extension repository directory, relative to the manifest
+    pub(super) repository: Option<Sourced<String>>,
+    /// `compress`: compression applied to extension files
+    pub(super) compression: Option<Sourced<Compression>>,
+    /// `abi-type`: ABI type of the extension
+    pub(super) abi_type: Option<Sourced<AbiType>>,
+    /// `duckdb-version`: DuckDB version recorded in the footer
+    pub(super) duckdb_version: Option<Sourced<String>>,
+    /// `c-api-version`: minimum C API version of C_STRUCT extensions
+    pub(super) c_api_version: Option<Sourced<String>>,
+    /// `duckdb-release`: DuckDB release naming the repository directory
+    pub(super) duckdb_release: Option<Sourced<String>>,
+    /// `platform`: DuckDB platform, regardless of the build target
+    pub(super) duckdb_platform: Option<Sourced<Platform>>,
+    /// `cargo-args`: arguments passed to `cargo build` when none are given
+    pub(super) cargo_args: Option<Sourced<Vec<String>>>,
+    /// `platforms`: target patterns mapped to DuckDB platforms, consulted before the built-in table,
+    /// one list per table in order of precedence
+    platforms: Vec<Vec<(String, Sourced<Platform>)>>,
+}
+
+impl DuckdbConfig {
+    /// Parses the `duckdb` table of a metadata value
+    ///
+    /// `section` names the table in error messages, e.g. `package.metadata.duckdb`.
+    /// A missing table yields the default configuration. Relative paths are
+    /// resolved against the directory of the manifest, except for
+    /// `extension-path`, which is resolved when the output directory is known.
+    pub(super) fn parse(metadata: &Value, section: &'static str, manifest: &Utf8Path) -> Result<Self, ToolsError> {
+        let invalid = |reason: String| ToolsError::InvalidConfig {
+            section: section.to_owned(),
+            manifest: manifest.to_string(),
+            reason,
+        };
+        let source = |key: &str| Source::Config {
+            key: key.to_owned(),
+            section,
+            manifest: manifest.to_owned(),
+        };
+        let string = |table: &Map<String, Value>, key: &str| {
+            string(table, key)
+                .map(|value| value.map(|value| Sourced::new(value, source(key))))
+                .map_err(invalid)
+        };
+        let mut config = Self::default();
+        let Some(table) = metadata.get("duckdb").filter(|table| !table.is_null()) else {
+            return Ok(config);
+        };
+        let table = table.as_object().ok_or_else(|| invalid("expected a table".to_owned()))?;
+        if let Some(key) = table.keys().find(|key| !KEYS.contains(&key.as_str())) {
+            return Err(invalid(format!("unknown key `{key}`; expected one of {}", KEYS.join(", "))));
+        }
+        let directory = manifest.parent().unwrap_or(Utf8Path::new(""));
+        config.extension_name = string(table, "extension-name")?;
+        config.extension_path = string(table, "extension-path")?;
+        if let Some(path) = config.extension_path.as_ref() {
+            check_template(&path.value).map_err(|error| invalid(error.to_string()))?;
+        }
+        config.out_dir = string(table, "out-dir")?.map(|path| path.map(|path| directory.join(path).to_string()));
+        config.repository = string(table, "repository")?.map(|path| path.map(|path| directory.join(path).to_string()));
+        config.abi_type = match string(table, "abi-type")? {
+            Some(abi_type) => Some(Sourced::new(
+                AbiType::from_str(&abi_type.value, false)
+                    .map_err(|_| invalid(format!("`abi-type` must be one of {}", abi_type_names().join(", "))))?,
+                abi_type.source,
+            )),
+            None => None,
+        };
+        config.compression = match string(table, "compress")? {
+            Some(compression) => Some(Sourced::new(
+                Compression::from_str(&compression.value, false)
+                    .map_err(|_| invalid("`compress` must be gzip".to_owned()))?,
+                compression.source,
+            )),
+            None => None,
+        };
+        config.duckdb_version = string(table, "duckdb-version")?;
+        config.c_api_version = string(table, "c-api-version")?;
+        config.duckdb_release = string(table, "duckdb-release")?;
+        config.duckdb_platform = string(table, "platform")?.map(|platform| platform.map(|platform| Platform::from(platform.as_str())));
+        if let Some(cargo_args) = table.get("cargo-args") {
+            let cargo_args = cargo_args
+                .as_array()
+                .and_then(|cargo_args| cargo_args.iter().map(|argument| argument.as_str().map(str::to_owned)).collect())
+                .ok_or_else(|| invalid("`cargo-args` must be an array of strings".to_owned()))?;
+            config.cargo_args = Some(Sourced::new(cargo_args, source("cargo-args")));
+        }
+        if let Some(platforms) = table.get("platforms") {
+            let platforms = platforms
+                .as_object()
+                .ok_or_else(|| invalid("`platforms` must map target patterns to DuckDB platforms".to_owned()))?;
+            let mut mappings = Vec::new();
+            for (pattern, platform) in platforms {
+                let platform = platform
+                    .as_str()
+                    .ok_or_else(|| invalid(format!("platform for `{pattern}` must be a string")))?;
+                let source = source(&format!("platforms.\"{pattern}\""));
+                mappings.push((pattern.to_owned(), Sourced::new(Platform::from(platform), source)));
+            }
+            config.platforms.push(mappings);
+        }
+        Ok(config)
+    }
+
+    /// Overlays this configuration on top of a fallback
+    ///
+    /// Platform patterns from both are kept; the fallback's are only consulted
+    /// for targets none of this configuration's patterns match.
+    pub(super) fn or(mut self, fallback: &Self) -> Self {
+        self.extension_name = self.extension_name.or_else(|| fallback.extension_name.to_owned());
+        self.extension_path = self.extension_path.or_else(|| fallback.extension_path.to_owned());
+        self.out_dir = self.out_dir.or_else(|| fallback.out_dir.to_owned());
+        self.repository = self.repository.or_else(|| fallback.repository.to_owned());
+        self.compression = self.compression.or_else(|| fallback.compression.to_owned());
+        self.abi_type = self.abi_type.or_else(|| fallback.abi_type.to_owned());
+        self.duckdb_version = self.duckdb_version.or_else(|| fallback.duckdb_version.to_owned());
+        self.c_api_version = self.c_api_version.or_else(|| fallback.c_api_version.to_owned());
+        self.duckdb_release = self.duckdb_release.or_else(|| fallback.duckdb_release.to_owned());
+        self.duckdb_platform = self.duckdb_platform.or_else(|| fallback.duckdb_platform.to_owned());
+        self.cargo_args = self.cargo_args.or_else(|| fallback.cargo_args.to_owned());
+        self.platforms.extend(fallback.platforms.iter().cloned());
+        self
+    }
+
+    /// Lists the DuckDB platforms of the `platforms` table
+    pub(super) fn platforms(&self) -> impl Iterator<Item = &Sourced<Platform>> {
+        self.platforms.iter().flatten().map(|(_, platform)| platform)
+    }
+
+    /// Looks up the DuckDB platform configured for a target
+    ///
+    /// Package mappings are consulted before workspace mappings. Within a
+    /// table, an exact match wins over `*` patterns; among patterns the
+    /// longest matching one is used.
+    pub(super) fn platform(&self, target: &str) -> Option<&Sourced<Platform>> {
+        self.platforms.iter().find_map(|mappings| {
+            mappings
+                .iter()
+                .filter(|(pattern, _)| matches(pattern, target))
+                .max_by_key(|(pattern, _)| (pattern == target, pattern.len()))
+                .map(|(_, platform)| platform)
+        })
+    }
+}
+
+/// Keys accepted in a `[*.metadata.duckdb]` table
+const KEYS: [&str; 12] = [
+    "extension-name",
+    "extension-path",
+    "out-dir",
+    "repository",
+    "compress",
+    "abi-type",
+    "duckdb-version",
+    "c-api-version",
+    "duckdb-release",
+    "platform",
+    "cargo-args",
+    "platforms",
+];
+
+/// Reads an optional string value
+fn string(table: &Map<String, Value>, key: &str) -> Result<Option<String>, String> {
+    match table.get(key) {
+        None => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.to_owned())),
+        Some(_) => Err(format!("`{key}` must be a string")),
+    }
+}
+
+/// Lists the names of all ABI types
+fn abi_type_names() -> Vec<String> {
+    AbiType::value_variants()
+        .iter()
+        .filter_map(|abi_type| abi_type.to_possible_value())
+        .map(|value| value.get_name().to_owned())
+        .collect()
+}
+
+/// Matches a target or package name against a pattern where `*` stands for any sequence of characters
+pub(super) fn matches(pattern: &str, target: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = target.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(section: &'static str, platforms: Value) -> DuckdbConfig {
+        let metadata = json!({ "duckdb": { "platforms": platforms } });
+        DuckdbConfig::parse(&metadata, section, Utf8Path::new("/project/Cargo.toml")).unwrap()
+    }
+
+    #[test]
+    fn package_platforms_win_over_longer_workspace_patterns() {
+        let workspace = config("workspace.metadata.duckdb", json!({
+            "x86_64-unknown-linux-*": "linux_amd64_workspace",
+            "aarch64-*": "linux_arm64_workspace",
+        }));
+        let package = config("package.metadata.duckdb", json!({
+            "x86_64*": "linux_amd64_package",
+            "x86_64-unknown-linux-gnu": "linux_amd64_exact",
+        }))
+        .or(&workspace);
+        let platform = |target| package.platform(target).map(|platform| platform.value.to_string());
+
+        assert_eq!(platform("x86_64-unknown-linux-gnu").as_deref(), Some("linux_amd64_exact"));
+        assert_eq!(platform("x86_64-unknown-linux-musl").as_deref(), Some("linux_amd64_package"));
+        assert_eq!(platform("aarch64-unknown-linux-gnu").as_deref(), Some("linux_arm64_workspace"));
+        assert_eq!(platform("wasm32-unknown-unknown"), None);
+    }
+}
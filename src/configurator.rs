@@ -0,0 +1,100 @@
+//! Inspection of the effective build settings
+//!
+//! This module implements the `duckdb-ext-config` subcommand. Its `show`
+//! command resolves the settings `duckdb-ext-build` would use, from the
+//! command line, environment variables, Cargo.toml metadata and detection,
+//! and prints each value together with the place it came from.
+
+use crate::builder::Builder;
+use crate::builder::BuilderOptions;
+use crate::config::Source;
+use crate::error::ToolsError;
+use clap::ArgMatches;
+use clap::Parser;
+use clap::Subcommand;
+use std::fmt::Display;
+
+/// Command line options for the `duckdb-ext-config` subcommand
+#[derive(Parser, Debug)]
+#[command(name = "duckdb-ext-config", version, author, about = "", long_about = "")]
+pub(super) struct ConfiguratorOptions {
+    #[command(subcommand)]
+    command: ConfiguratorCommand,
+}
+
+/// Commands of the `duckdb-ext-config` subcommand
+#[derive(Subcommand, Debug)]
+enum ConfiguratorCommand {
+    /// Print the effective build settings and where each value came from
+    Show(BuilderOptions),
+}
+
+impl ConfiguratorOptions {
+    /// Builds the options from parsed matches
+    ///
+    /// The build options of `show` keep track of the values read from
+    /// environment variables, see [`BuilderOptions::from_matches`].
+    pub(super) fn from_matches(matches: &ArgMatches) -> Self {
+        let command = match matches.subcommand() {
+            Some(("show", matches)) => ConfiguratorCommand::Show(BuilderOptions::from_matches(matches)),
+            _ => unreachable!("clap requires a known subcommand"),
+        };
+        Self { command }
+    }
+}
+
+/// Prints the settings a build would use without building anything
+pub(super) struct Configurator {
+    /// Builder resolving the settings exactly as `duckdb-ext-build` does
+    builder: Builder,
+}
+
+impl TryFrom<ConfiguratorOptions> for Configurator {
+    type Error = ToolsError;
+
+    fn try_from(parameters: ConfiguratorOptions) -> Result<Self, Self::Error> {
+        let ConfiguratorCommand::Show(options) = parameters.command;
+        Ok(Self {
+            builder: Builder::try_from(options)?,
+        })
+    }
+}
+
+impl Configurator {
+    /// Prints the effective settings of every extension package and build target
+    ///
+    /// Library paths are only known after building, so default extension
+    /// paths are shown relative to `<library directory>`.
+    pub(super) fn show(&self) -> Result<(), ToolsError> {
+        let cargo_args = self.builder.cargo_args();
+        let args = match cargo_args.value.is_empty() {
+            true => "(none)".to_owned(),
+            false => cargo_args.value.join(" "),
+        };
+        field("Cargo Args", args, &cargo_args.source);
+        for package in self.builder.packages() {
+            println!();
+            println!("          Package     {} v{}", package.name, package.version);
+            for target in self.builder.build_targets() {
+                let settings = self.builder.extension_settings(package, &target, None)?;
+                field("Build Target", &target.value, &target.source);
+                field("Extension Name", &settings.extension_name.value, &settings.extension_name.source);
+                field("Extension File", &settings.extension_path.value, &settings.extension_path.source);
+                field("Extension Version", &settings.extension_version.value, &settings.extension_version.source);
+                field("ABI Type", settings.abi_type.value, &settings.abi_type.source);
+                field("DuckDB Version", &settings.duckdb_version.value, &settings.duckdb_version.source);
+                field("DuckDB Platform", &settings.duckdb_platform.value, &settings.duckdb_platform.source);
+                match settings.compression.as_ref() {
+                    Some(compression) => field("Compression", compression.value, &compression.source),
+                    None => field("Compression", "none", &Source::Default),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Prints a setting aligned like the output of `duckdb-ext-inspect`
+fn field(label: &str, value: impl Display, source: &Source) {
+    println!("{label:>17}     {value} (from {source})");
+}
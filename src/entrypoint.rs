@@ -0,0 +1,300 @@
+//! Detection of the duckdb-rs extension entrypoint
+//!
+//! Extensions built with duckdb-rs declare their name and minimum DuckDB
+//! version through the `#[duckdb_entrypoint_c_api]` attribute. This module
+//! reads those values from the sources of a cdylib target so that they can
+//! serve as packaging defaults.
+//!
+//! The sources are parsed with `syn`, starting at the target's root file and
+//! following `mod` declarations into their files, so comments and strings are
+//! never mistaken for the attribute. Macros are not expanded, and of `#[cfg]`
+//! predicates only those that are false in every build are honored:
+//! `any()`, `not(all())` and `test`. Items under any other predicate are
+//! considered enabled.
+
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, ExprLit, Item, ItemMod, Lit, Meta, MetaNameValue, Token};
+
+/// Name of the duckdb-rs entrypoint attribute macro
+const ATTRIBUTE: &str = "duckdb_entrypoint_c_api";
+
+/// Values declared by the `#[duckdb_entrypoint_c_api]` attribute
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(super) struct Entrypoint {
+    /// The `ext_name` argument: the name DuckDB loads the extension by
+    pub(super) ext_name: Option<String>,
+    /// The `min_duckdb_version` argument: the minimum C API version
+    pub(super) min_duckdb_version: Option<String>,
+}
+
+impl Entrypoint {
+    /// Reads the entrypoint attribute from a target's sources
+    ///
+    /// Returns `None` if no enabled function uses the attribute, or if a
+    /// file is not valid Rust; Cargo reports the latter when building.
+    pub(super) fn read(src_path: &Utf8Path) -> Result<Option<Self>, std::io::Error> {
+        let directory = src_path.parent().unwrap_or(Utf8Path::new(""));
+        Self::read_module(src_path, directory)
+    }
+
+    /// Reads a module file whose child modules live in `directory`
+    fn read_module(path: &Utf8Path, directory: &Utf8Path) -> Result<Option<Self>, std::io::Error> {
+        let source = std::fs::read_to_string(path)?;
+        let base = path.parent().unwrap_or(Utf8Path::new(""));
+        Self::parse(&source, base, directory)
+    }
+
+    /// Extracts the attribute arguments from the Rust source of a module
+    ///
+    /// `base` is the directory of the source file, against which `#[path]`
+    /// attributes resolve; `directory` holds its out-of-line child modules.
+    fn parse(source: &str, base: &Utf8Path, directory: &Utf8Path) -> Result<Option<Self>, std::io::Error> {
+        match syn::parse_file(source) {
+            Ok(file) => Self::find(&file.items, base, directory),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Searches items and their child modules for the attribute
+    fn find(items: &[Item], base: &Utf8Path, directory: &Utf8Path) -> Result<Option<Self>, std::io::Error> {
+        for item in items {
+            let found = match item {
+                Item::Fn(function) if is_enabled(&function.attrs) => {
+                    function.attrs.iter().find_map(Self::from_attribute)
+                }
+                Item::Mod(module) if is_enabled(&module.attrs) => match &module.content {
+                    Some((_, items)) => {
+                        let directory = directory.join(module.ident.to_string());
+                        Self::find(items, &directory, &directory)?
+                    }
+                    None => match module_file(module, base, directory) {
+                        Some((path, directory)) => Self::read_module(&path, &directory)?,
+                        None => None,
+                    },
+                },
+                _ => None,
+            };
+            if found.is_some() {
+                return Ok(found);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parses the entrypoint attribute, which may carry a path prefix
+    ///
+    /// The attribute may be written without arguments, e.g. in
+    /// `#[duckdb::duckdb_entrypoint_c_api]`, in which case both values are
+    /// left unset.
+    fn from_attribute(attribute: &Attribute) -> Option<Self> {
+        if attribute.path().segments.last().is_none_or(|segment| segment.ident != ATTRIBUTE) {
+            return None;
+        }
+
+        let mut entrypoint = Self::default();
+        let Meta::List(list) = &attribute.meta else {
+            return Some(entrypoint);
+        };
+        let arguments =
+            list.parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated).unwrap_or_default();
+        for argument in arguments {
+            let Expr::Lit(ExprLit { lit: Lit::Str(value), .. }) = &argument.value else {
+                continue;
+            };
+            if argument.path.is_ident("ext_name") {
+                entrypoint.ext_name = Some(value.value());
+            } else if argument.path.is_ident("min_duckdb_version") {
+                entrypoint.min_duckdb_version = Some(value.value());
+            }
+        }
+        Some(entrypoint)
+    }
+}
+
+/// Locates the file of an out-of-line module
+///
+/// Returns the file together with the directory of its own child modules,
+/// or `None` if the file does not exist, e.g. because it is generated.
+fn module_file(module: &ItemMod, base: &Utf8Path, directory: &Utf8Path) -> Option<(Utf8PathBuf, Utf8PathBuf)> {
+    let explicit = module.attrs.iter().find_map(|attribute| match &attribute.meta {
+        Meta::NameValue(MetaNameValue { path, value: Expr::Lit(ExprLit { lit: Lit::Str(value), .. }), .. })
+            if path.is_ident("path") =>
+        {
+            Some(value.value())
+        }
+        _ => None,
+    });
+    if let Some(path) = explicit {
+        let path = base.join(path);
+        let directory = path.parent().unwrap_or(Utf8Path::new("")).to_owned();
+        return path.is_file().then_some((path, directory));
+    }
+
+    let name = module.ident.to_string();
+    let directory = directory.join(&name);
+    [directory.with_extension("rs"), directory.join("mod.rs")]
+        .into_iter()
+        .find(|path| path.is_file())
+        .map(|path| (path, directory))
+}
+
+/// Tells whether no `#[cfg]` attribute disables an item in every build
+fn is_enabled(attributes: &[Attribute]) -> bool {
+    attributes
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("cfg"))
+        .all(|attribute| attribute.parse_args::<Meta>().map_or(true, |predicate| evaluate(&predicate) != Some(false)))
+}
+
+/// Evaluates a `cfg` predicate as far as it holds for every build
+///
+/// Returns `None` for predicates that depend on the build configuration.
+fn evaluate(predicate: &Meta) -> Option<bool> {
+    match predicate {
+        Meta::Path(path) if path.is_ident("test") => Some(false),
+        Meta::List(list) => {
+            let operands = list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated).ok()?;
+            let values: Vec<_> = operands.iter().map(evaluate).collect();
+            if list.path.is_ident("any") {
+                if values.contains(&Some(true)) {
+                    Some(true)
+                } else {
+                    values.iter().all(|value| *value == Some(false)).then_some(false)
+                }
+            } else if list.path.is_ident("all") {
+                if values.contains(&Some(false)) {
+                    Some(false)
+                } else {
+                    values.iter().all(|value| *value == Some(true)).then_some(true)
+                }
+            } else if list.path.is_ident("not") && values.len() == 1 {
+                values[0].map(|value| !value)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Option<Entrypoint> {
+        Entrypoint::parse(source, Utf8Path::new(""), Utf8Path::new("")).unwrap()
+    }
+
+    fn entrypoint(ext_name: &str, min_duckdb_version: &str) -> Option<Entrypoint> {
+        Some(Entrypoint {
+            ext_name: Some(ext_name.to_owned()),
+            min_duckdb_version: Some(min_duckdb_version.to_owned()),
+        })
+    }
+
+    #[test]
+    fn parses_multi_line_attribute() {
+        let source = r#"
+            #[duckdb_entrypoint_c_api(
+                ext_name = "quack",
+                min_duckdb_version = "v1.2.0",
+            )]
+            pub unsafe fn extension_entrypoint(connection: Connection) -> Result<(), Box<dyn Error>> {
+                Ok(())
+            }
+        "#;
+        assert_eq!(parse(source), entrypoint("quack", "v1.2.0"));
+    }
+
+    #[test]
+    fn parses_path_prefixed_attribute() {
+        let source = r#"
+            #[duckdb::duckdb_entrypoint_c_api(ext_name = "quack", min_duckdb_version = "v1.2.0")]
+            fn entrypoint() {}
+        "#;
+        assert_eq!(parse(source), entrypoint("quack", "v1.2.0"));
+    }
+
+    #[test]
+    fn keeps_values_with_parentheses() {
+        let source = r#"
+            #[duckdb_entrypoint_c_api(ext_name = "quack", min_duckdb_version = "v1.2.0 (dev)")]
+            fn entrypoint() {}
+        "#;
+        assert_eq!(parse(source), entrypoint("quack", "v1.2.0 (dev)"));
+    }
+
+    #[test]
+    fn parses_missing_arguments() {
+        let expected = Some(Entrypoint::default());
+        assert_eq!(parse("#[duckdb_entrypoint_c_api] fn entrypoint() {}"), expected);
+        assert_eq!(parse("#[duckdb_entrypoint_c_api()] fn entrypoint() {}"), expected);
+        assert_eq!(
+            parse(r#"#[duckdb_entrypoint_c_api(ext_name = "quack")] fn entrypoint() {}"#),
+            Some(Entrypoint { ext_name: Some("quack".to_owned()), min_duckdb_version: None })
+        );
+    }
+
+    #[test]
+    fn ignores_commented_out_attributes() {
+        let source = r##"
+            // #[duckdb_entrypoint_c_api(ext_name = "line")]
+            /*
+            #[duckdb_entrypoint_c_api(ext_name = "block")]
+            */
+            const USAGE: &str = "#[duckdb_entrypoint_c_api(ext_name = \"string\")]";
+            fn entrypoint() {}
+        "##;
+        assert_eq!(parse(source), None);
+    }
+
+    #[test]
+    fn ignores_disabled_items() {
+        let source = r#"
+            #[cfg(any())]
+            #[duckdb_entrypoint_c_api(ext_name = "any")]
+            fn disabled() {}
+
+            #[cfg(test)]
+            mod tests {
+                #[duckdb_entrypoint_c_api(ext_name = "test")]
+                fn disabled() {}
+            }
+
+            #[cfg(not(any(unix, all())))]
+            #[duckdb_entrypoint_c_api(ext_name = "not")]
+            fn disabled() {}
+
+            #[cfg(feature = "loadable")]
+            #[duckdb_entrypoint_c_api(ext_name = "quack", min_duckdb_version = "v1.2.0")]
+            fn enabled() {}
+        "#;
+        assert_eq!(parse(source), entrypoint("quack", "v1.2.0"));
+    }
+
+    #[test]
+    fn finds_attribute_in_submodules() {
+        let inline = r#"
+            mod extension {
+                #[duckdb_entrypoint_c_api(ext_name = "quack", min_duckdb_version = "v1.2.0")]
+                fn entrypoint() {}
+            }
+        "#;
+        assert_eq!(parse(inline), entrypoint("quack", "v1.2.0"));
+
+        let root = std::env::temp_dir().join(format!("quack-entrypoint-{}", std::process::id()));
+        let root = Utf8PathBuf::from_path_buf(root).unwrap();
+        std::fs::create_dir_all(root.join("src/extension")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "mod extension;\nmod generated;\n").unwrap();
+        std::fs::write(root.join("src/extension.rs"), "#[path = \"extension/entry.rs\"]\nmod entry;\n").unwrap();
+        std::fs::write(
+            root.join("src/extension/entry.rs"),
+            r#"#[duckdb_entrypoint_c_api(ext_name = "quack", min_duckdb_version = "v1.2.0")] fn entrypoint() {}"#,
+        )
+        .unwrap();
+        let found = Entrypoint::read(&root.join("src/lib.rs"));
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(found.unwrap(), entrypoint("quack", "v1.2.0"));
+    }
+}
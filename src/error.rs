@@ -3,6 +3,7 @@
 //! This module defines the error types used throughout the application,
 //! providing unified error handling for both I/O operations and cargo metadata processing.
 
+use crate::validation::ValidationError;
 use thiserror::Error;
 
 /// Error type for the cargo-duckdb-ext-tools
@@ -18,4 +19,140 @@ pub(super) enum ToolsError {
     /// Wraps errors from cargo metadata operations
     #[error("{0}")]
     MetadataError(#[from] cargo_metadata::Error),
+
+    /// Wraps errors from encoding or decoding the metadata footer
+    #[error("{0}")]
+    FooterError(#[from] cargo_duckdb_ext_tools::FooterError),
+
+    /// One or more metadata values failed validation
+    #[error("invalid extension metadata:{}", .0.iter().map(|error| format!("\n  - {error}")).collect::<String>())]
+    InvalidMetadata(Vec<ValidationError>),
+
+    /// The ABI type stored in an existing footer is not known
+    #[error("unsupported ABI type `{0}` in existing footer")]
+    UnknownAbiType(String),
+
+    /// An override disagrees with the `#[duckdb_entrypoint_c_api]` attribute
+    #[error("{package} declares {attribute} = \"{declared}\" in #[duckdb_entrypoint_c_api], but {given} was given on the command line or in Cargo.toml")]
+    EntrypointMismatch {
+        package: String,
+        attribute: &'static str,
+        declared: String,
+        given: String,
+    },
+
+    /// The input library cannot be parsed as an object file
+    #[error("cannot read {path} as a dynamic library: {reason}")]
+    UnreadableLibrary { path: String, reason: String },
+
+    /// The input library does not export the init function DuckDB will call
+    #[error("{library} does not export {expected}, which DuckDB calls to load the extension `{extension}`; rename the output file to match the exported entry point or pass --no-verify-symbols")]
+    MissingEntrypoint {
+        library: String,
+        extension: String,
+        expected: String,
+    },
+
+    /// The input library was built for a different platform than declared
+    #[error("{library} was built for {detected} but is being packed for {platform}; pass --force to pack it anyway")]
+    PlatformMismatch {
+        library: String,
+        detected: String,
+        platform: String,
+    },
+
+    /// `cargo build` exited unsuccessfully
+    #[error("cargo build failed ({})", .0.map_or("terminated by signal".to_owned(), |code| format!("exit code {code}")))]
+    BuildFailed(Option<i32>),
+
+    /// `cargo build` succeeded but produced no extension to package
+    #[error("no cdylib artifacts found for packages {}", if .0.is_empty() { "(the workspace has no cdylib packages)".to_owned() } else { .0.join(", ") })]
+    NoArtifacts(Vec<String>),
+
+    /// The program was invoked under a name that maps to no subcommand
+    #[error("`{0}` is not a known command; invoke it as cargo-duckdb-ext-build, -pack, -inspect, -unpack, -retag, -config or -serve")]
+    UnsupportedTask(String),
+
+    /// Quiet mode was configured twice with different values
+    #[error("console output was already configured with quiet = {0}")]
+    QuietConflict(bool),
+
+    /// No DuckDB version was given and none can be derived for a package
+    #[error("no duckdb or libduckdb-sys dependency found for {0}; pass --duckdb-version")]
+    MissingDuckdbVersion(String),
+
+    /// A package links several DuckDB versions, so none can be chosen
+    #[error("{package} depends on conflicting DuckDB versions ({}); align them so that a single DuckDB is linked", candidates.join(", "))]
+    ConflictingDuckdbVersions { package: String, candidates: Vec<String> },
+
+    /// A `--package` or `--lib-name` value selects no extension package
+    #[error("`{option} {value}` matches no workspace member with a cdylib target")]
+    NoMatchingPackage { option: &'static str, value: String },
+
+    /// An extension path template uses an unknown or unclosed placeholder
+    #[error("unknown placeholder `{placeholder}` in extension path `{template}`; expected {{name}}, {{version}}, {{duckdb_version}} or {{platform}}")]
+    UnknownPlaceholder { template: String, placeholder: String },
+
+    /// The DuckDB release naming the repository directory of a C_STRUCT extension is unknown
+    #[error("extension repositories are laid out by DuckDB release, but none is known for the C_STRUCT extension {extension}; {hint}")]
+    MissingDuckdbRelease { extension: String, hint: &'static str },
+
+    /// The directory given to `duckdb-ext-serve` does not exist
+    #[error("extension repository {0} is not a directory")]
+    RepositoryNotFound(String),
+
+    /// A fixed extension path would be shared by several extensions
+    #[error("{count} extensions would be written to {path} (from {origin}); select a single package and build target, or use a template such as {{name}}-{{platform}}.duckdb_extension")]
+    ExtensionPathConflict { path: String, origin: String, count: usize },
+
+    /// The build target has no corresponding DuckDB platform
+    #[error("target {triple} has no DuckDB platform (unsupported {component}); pass --duckdb-platform or map it in [package.metadata.duckdb.platforms]")]
+    UnsupportedTarget { triple: String, component: &'static str },
+
+    /// A cargo configuration file or `--config` override cannot be parsed
+    #[error("cannot read cargo configuration {path}: {reason}")]
+    InvalidCargoConfig { path: String, reason: String },
+
+    /// A `[*.metadata.duckdb]` table has an unexpected shape
+    #[error("invalid [{section}] in {manifest}: {reason}")]
+    InvalidConfig {
+        section: String,
+        manifest: String,
+        reason: String,
+    },
+
+    /// An artifact of a multi-target build cannot be matched to its target
+    #[error("cannot tell which of the targets {targets} {library} was built for; pass --duckdb-platform")]
+    AmbiguousTarget { library: String, targets: String },
+
+    /// The input library already ends with a metadata footer
+    #[error("{0} already contains a DuckDB extension footer; pass --replace to overwrite it")]
+    FooterAlreadyPresent(String),
+
+    /// The unpacked library would overwrite the extension it is read from
+    #[error("{0} is both the extension and the library path; pass a different --library-path")]
+    OutputIsInput(String),
+
+    /// The default library path of `duckdb-ext-unpack` is already taken
+    #[error("{0} already exists; pass --library-path to overwrite it or to choose another file")]
+    LibraryExists(String),
+
+    /// The file does not end with a DuckDB extension metadata footer
+    #[error("{0} is not a DuckDB extension: metadata footer not found")]
+    MissingFooter(String),
+}
+
+impl ToolsError {
+    /// Returns the process exit code reported for this error
+    ///
+    /// Build failures and missing artifacts get their own codes so that CI
+    /// scripts can tell them apart from packaging problems. Code 2 is left
+    /// to clap for command line usage errors.
+    pub(super) fn exit_code(&self) -> u8 {
+        match self {
+            ToolsError::BuildFailed(_) => 3,
+            ToolsError::NoArtifacts(_) => 4,
+            _ => 1,
+        }
+    }
 }
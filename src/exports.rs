@@ -0,0 +1,76 @@
+//! Verification of the entry point exported by a dynamic library
+//!
+//! DuckDB loads `<name>.duckdb_extension` by calling an init function whose
+//! name is derived from the file name and the ABI type. This module checks
+//! the export table of the library before it is packed, so that a mismatch
+//! is reported when packing instead of as a confusing error at load time.
+
+use crate::abi::AbiType;
+use crate::architecture::WASM_MAGIC;
+use crate::console;
+use crate::error::ToolsError;
+use crate::logger::QUITE;
+use object::Object;
+use std::path::Path;
+
+/// Derives the extension name DuckDB uses for an extension file
+///
+/// DuckDB takes the lowercased file name up to the first dot, so
+/// `quack.duckdb_extension` is loaded as `quack`.
+pub(super) fn extension_name(extension_path: &str) -> String {
+    Path::new(extension_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split('.').next())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Lists the init functions DuckDB looks for, in order of preference
+pub(super) fn entrypoint_symbols(extension_name: &str, abi_type: AbiType) -> Vec<String> {
+    match abi_type {
+        AbiType::CStruct | AbiType::CStructUnstable => vec![format!("{extension_name}_init_c_api")],
+        AbiType::Cpp => vec![
+            format!("{extension_name}_duckdb_cpp_init"),
+            format!("{extension_name}_init"),
+        ],
+    }
+}
+
+/// Confirms that the library exports the init function for an extension name
+///
+/// The export table is read from the ELF, Mach-O or PE headers of the
+/// library. Mach-O symbols carry a leading underscore, which is ignored.
+/// WebAssembly modules are skipped, as their exports cannot be read yet.
+pub(super) fn verify_entrypoint(library_path: &str, extension_name: &str, abi_type: AbiType) -> Result<(), ToolsError> {
+    let expected = entrypoint_symbols(extension_name, abi_type);
+    let unreadable = |error: object::Error| ToolsError::UnreadableLibrary {
+        path: library_path.to_owned(),
+        reason: error.to_string(),
+    };
+
+    let data = std::fs::read(library_path)?;
+    if data.starts_with(WASM_MAGIC) {
+        console!("     Warning skipping entry point verification for WebAssembly module {library_path}");
+        return Ok(());
+    }
+    let file = object::File::parse(&*data).map_err(unreadable)?;
+    let exports = file.exports().map_err(unreadable)?;
+
+    let symbol = expected.iter().find(|symbol| {
+        exports.iter().any(|export| {
+            export.name() == symbol.as_bytes() || export.name().strip_prefix(b"_") == Some(symbol.as_bytes())
+        })
+    });
+    match symbol {
+        Some(symbol) => {
+            console!("   Verifying Entry Point ({symbol})");
+            Ok(())
+        }
+        None => Err(ToolsError::MissingEntrypoint {
+            library: library_path.to_owned(),
+            extension: extension_name.to_owned(),
+            expected: expected.join(" or "),
+        }),
+    }
+}
@@ -6,14 +6,22 @@
 
 use crate::console;
 use crate::logger::QUITE;
+use cargo_duckdb_ext_tools::FOOTER_SIZE;
+use std::fs::canonicalize;
 use std::fs::copy;
+use std::fs::create_dir_all;
 use std::fs::File;
 use std::fs::OpenOptions;
+use std::io::Read;
+use std::path::Path;
 
 /// Creates a duplicate of a file and opens it in append mode
 ///
 /// This function is used to create the extension file by copying the
 /// original dynamic library and then opening it for metadata appending.
+/// Missing parent directories of the target are created. If both paths
+/// refer to the same file, it is opened in place instead of copied onto
+/// itself, which would empty it.
 ///
 /// # Arguments
 /// * `source` - Path to the source dynamic library file
@@ -22,10 +30,56 @@ use std::fs::OpenOptions;
 /// # Returns
 /// A `File` handle opened in append mode for writing metadata
 pub(super) fn open_duplicate(source: &str, target: &str) -> Result<File, std::io::Error> {
+    if is_same_file(source, target) {
+        console!("     Opening Extension File ({target})");
+        return OpenOptions::new()
+            .append(true)
+            .open(target);
+    }
     console!("     Copying Library File ({source})");
     console!("     Copying Extension File ({target})");
+    if let Some(directory) = Path::new(target).parent() {
+        create_dir_all(directory)?;
+    }
     copy(source, target)?;
     OpenOptions::new()
         .append(true)
         .open(target)
 }
+
+/// Returns `true` if both paths resolve to the same existing file
+pub(super) fn is_same_file(source: &str, target: &str) -> bool {
+    match (canonicalize(source), canonicalize(target)) {
+        (Ok(source), Ok(target)) => source == target,
+        _ => false,
+    }
+}
+
+/// Removes the trailing metadata footer from an extension file
+///
+/// This truncates the file by exactly 534 bytes so that a new footer
+/// can be appended in place of the old one.
+///
+/// # Arguments
+/// * `file` - Writable handle to a file known to end with a footer
+pub(super) fn strip_footer(file: &File) -> Result<(), std::io::Error> {
+    console!("    Removing Existing Footer");
+    let length = file.metadata()?.len();
+    file.set_len(length.saturating_sub(FOOTER_SIZE as u64))
+}
+
+/// Copies an extension file without its trailing metadata footer
+///
+/// This recovers the original dynamic library from a packed extension.
+///
+/// # Arguments
+/// * `source` - Path to an extension file known to end with a footer
+/// * `target` - Path where the dynamic library should be created
+pub(super) fn copy_without_footer(source: &str, target: &str) -> Result<(), std::io::Error> {
+    console!("     Copying Extension File ({source})");
+    console!("     Copying Library File ({target})");
+    let file = File::open(source)?;
+    let length = file.metadata()?.len().saturating_sub(FOOTER_SIZE as u64);
+    std::io::copy(&mut file.take(length), &mut File::create(target)?)?;
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! DuckDB extension metadata inspector
+//!
+//! This module implements the `duckdb-ext-inspect` subcommand, which reads
+//! back the 534-byte footer written by the packer and prints every field
+//! of an existing DuckDB extension file.
+
+use crate::error::ToolsError;
+use cargo_duckdb_ext_tools::ExtensionMetadata;
+use cargo_duckdb_ext_tools::FooterError;
+use clap::Parser;
+use serde_json::json;
+use std::fs::File;
+
+/// Command line options for the `duckdb-ext-inspect` subcommand
+///
+/// This struct defines the parameters required to decode the metadata
+/// footer of an existing DuckDB extension file.
+#[derive(Parser, Debug)]
+#[command(name = "duckdb-ext-inspect", version, author, about = "", long_about = "")]
+pub(super) struct InspectorOptions {
+    /// Path to the extension file to inspect
+    #[arg(value_name = "EXTENSION-PATH")]
+    extension_path: String,
+
+    /// Print the metadata as JSON
+    #[arg(short = 'j', long, default_value_t = false)]
+    json: bool,
+}
+
+/// Opens an extension file and decodes its metadata footer
+///
+/// Missing or truncated footers are reported with the offending path.
+pub(super) fn read_metadata(path: &str) -> Result<ExtensionMetadata, ToolsError> {
+    let file = File::open(path)?;
+    ExtensionMetadata::read_from(file).map_err(|error| match error {
+        FooterError::Truncated(_) | FooterError::MissingSignature => ToolsError::MissingFooter(path.to_owned()),
+        error => error.into(),
+    })
+}
+
+/// Renders the signature area as hex, or `None` if it is unsigned
+fn signature_hex(metadata: &ExtensionMetadata) -> Option<String> {
+    metadata
+        .is_signed()
+        .then(|| metadata.signature.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Renders the metadata as a JSON value
+pub(super) fn metadata_json(metadata: &ExtensionMetadata) -> serde_json::Value {
+    json!({
+        "abi_type": metadata.abi_type,
+        "extension_version": metadata.extension_version,
+        "duckdb_version": metadata.duckdb_version,
+        "duckdb_platform": metadata.duckdb_platform,
+        "metadata_version": metadata.metadata_version,
+        "reserved": metadata.reserved,
+        "signature": signature_hex(metadata),
+    })
+}
+
+/// Prints the metadata footer of an existing DuckDB extension
+pub(super) struct Inspector {
+    /// Path of the extension file being inspected
+    extension_path: String,
+    /// Whether to print JSON instead of human-readable text
+    json: bool,
+}
+
+impl From<InspectorOptions> for Inspector {
+    fn from(parameters: InspectorOptions) -> Self {
+        Self {
+            extension_path: parameters.extension_path,
+            json: parameters.json,
+        }
+    }
+}
+
+impl Inspector {
+    /// Decodes the footer and prints it to standard output
+    pub(super) fn inspect(&self) -> Result<(), ToolsError> {
+        let metadata = read_metadata(&self.extension_path)?;
+        if self.json {
+            println!("{:#}", metadata_json(&metadata));
+        } else {
+            println!("   Extension File     {}", self.extension_path);
+            println!("         ABI Type     {}", metadata.abi_type);
+            println!("Extension Version     {}", metadata.extension_version);
+            println!("   DuckDB Version     {}", metadata.duckdb_version);
+            println!("  DuckDB Platform     {}", metadata.duckdb_platform);
+            println!(" Metadata Version     {}", metadata.metadata_version);
+            println!("         Reserved     {:?}", metadata.reserved);
+            println!(
+                "        Signature     {}",
+                signature_hex(&metadata).unwrap_or_else(|| "(unsigned)".to_owned())
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+//! Library interface for cargo-duckdb-ext-tools
+//!
+//! This crate exposes the DuckDB extension metadata codec used by the
+//! `cargo duckdb-ext-*` subcommands, so that other tools can produce and
+//! check extension footers without shelling out to the binaries.
+//!
+//! ```no_run
+//! use cargo_duckdb_ext_tools::ExtensionMetadata;
+//! use std::fs::File;
+//! use std::fs::OpenOptions;
+//!
+//! let metadata = ExtensionMetadata::new("C_STRUCT_UNSTABLE", "v1.0.0", "v1.4.2", "linux_amd64");
+//! let file = OpenOptions::new().append(true).open("quack.duckdb_extension")?;
+//! metadata.append_to(file)?;
+//!
+//! let decoded = ExtensionMetadata::read_from(File::open("quack.duckdb_extension")?)?;
+//! assert_eq!(decoded, metadata);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod metadata;
+
+pub use metadata::has_footer;
+pub use metadata::ExtensionMetadata;
+pub use metadata::FooterError;
+pub use metadata::MetadataField;
+pub use metadata::FIELD_SIZE;
+pub use metadata::FOOTER_SIZE;
+pub use metadata::METADATA_VERSION;
+pub use metadata::SIGNATURE_SIZE;
+pub use metadata::START_SIGNATURE;
@@ -3,6 +3,7 @@
 //! This module provides a simple logging system with support for quiet mode,
 //! allowing users to suppress console output when desired.
 
+use crate::error::ToolsError;
 use std::sync::OnceLock;
 
 /// Global flag controlling whether console output should be suppressed
@@ -11,6 +12,16 @@ use std::sync::OnceLock;
 /// This is used to implement the `--quiet` command line option.
 pub(super) static QUITE: OnceLock<bool> = OnceLock::new();
 
+/// Configures quiet mode for the rest of the process
+///
+/// Setting the same value again is allowed; a conflicting value is an error.
+pub(super) fn set_quiet(quiet: bool) -> Result<(), ToolsError> {
+    match QUITE.set(quiet) {
+        Err(_) if QUITE.get() != Some(&quiet) => Err(ToolsError::QuietConflict(!quiet)),
+        _ => Ok(()),
+    }
+}
+
 /// Prints to standard output only when quiet mode is disabled
 ///
 /// This macro behaves like `println!` but respects the global quiet flag.
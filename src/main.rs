@@ -1,22 +1,49 @@
 //! Main entry point for cargo-duckdb-ext-tools
 //!
-//! This binary provides two cargo subcommands:
+//! This binary provides the following cargo subcommands:
 //! - `duckdb-ext-pack`: Appends DuckDB extension metadata to dynamic libraries
 //! - `duckdb-ext-build`: Builds and packages DuckDB extensions in one step
+//! - `duckdb-ext-inspect`: Prints the metadata of an existing extension
+//! - `duckdb-ext-unpack`: Recovers the dynamic library from an extension
+//! - `duckdb-ext-retag`: Rewrites metadata fields of an extension in place
+//! - `duckdb-ext-config`: Shows the effective build settings and their sources
+//! - `duckdb-ext-serve`: Serves an extension repository over HTTP
 
+mod abi;
+mod architecture;
 mod builder;
+mod compression;
+mod config;
+mod configurator;
+mod entrypoint;
 mod error;
+mod exports;
 mod fs;
+mod inspector;
 mod logger;
 mod packer;
+mod platform;
+mod repository;
+mod retagger;
+mod server;
+mod target;
 mod task;
+mod template;
+mod unpacker;
+mod validation;
 
 use crate::task::Task;
-use anyhow::Result;
+use std::process::ExitCode;
 
 /// Main entry point that delegates to the appropriate task based on command line arguments
-fn main() -> Result<()> {
-    let task = Task::new();
-    task.execute()?;
-    Ok(())
+///
+/// Errors are printed to stderr and mapped to the exit code of their kind.
+fn main() -> ExitCode {
+    match Task::new().and_then(|task| task.execute()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {error}");
+            ExitCode::from(error.exit_code())
+        }
+    }
 }
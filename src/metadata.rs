@@ -0,0 +1,348 @@
+//! DuckDB extension metadata footer codec
+//!
+//! This module implements the 534-byte footer that DuckDB expects at the end
+//! of every extension file. The footer consists of a 22-byte start signature
+//! followed by eight 32-byte metadata fields and a 256-byte signature area.
+
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use thiserror::Error;
+
+/// Fixed byte sequence that starts every DuckDB extension metadata footer
+pub const START_SIGNATURE: [u8; 22] = [
+    0, 147, 4, 16, 100, 117, 99, 107, 100, 98, 95, 115, 105, 103, 110, 97, 116, 117, 114, 101, 128, 4,
+];
+
+/// Size of each metadata field in bytes
+pub const FIELD_SIZE: usize = 32;
+
+/// Size of the signature area at the end of the footer in bytes
+pub const SIGNATURE_SIZE: usize = 8 * FIELD_SIZE;
+
+/// Total size of the metadata footer in bytes
+pub const FOOTER_SIZE: usize = START_SIGNATURE.len() + 8 * FIELD_SIZE + SIGNATURE_SIZE;
+
+/// Metadata format version written by current DuckDB releases
+pub const METADATA_VERSION: &str = "4";
+
+/// Error type for encoding and decoding extension metadata footers
+#[derive(Error, Debug)]
+pub enum FooterError {
+    /// Wraps I/O errors that occur while reading or writing a footer
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    /// The input is too short to contain a metadata footer
+    #[error("input is {0} bytes long, shorter than the {FOOTER_SIZE}-byte metadata footer")]
+    Truncated(usize),
+
+    /// The footer does not begin with the DuckDB start signature
+    #[error("DuckDB extension start signature not found")]
+    MissingSignature,
+
+    /// A metadata value does not fit into its 32-byte field
+    #[error("metadata value `{0}` is longer than {FIELD_SIZE} bytes")]
+    FieldTooLong(String),
+}
+
+/// Text fields of the metadata footer that can be rewritten individually
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataField {
+    /// ABI type specification
+    AbiType,
+    /// Version string of the extension
+    ExtensionVersion,
+    /// DuckDB version compatibility
+    DuckdbVersion,
+    /// Target platform identifier
+    DuckdbPlatform,
+}
+
+impl MetadataField {
+    /// Byte offset of the field from the start of the footer
+    pub fn offset(self) -> usize {
+        let index = match self {
+            MetadataField::AbiType => 3,
+            MetadataField::ExtensionVersion => 4,
+            MetadataField::DuckdbVersion => 5,
+            MetadataField::DuckdbPlatform => 6,
+        };
+        START_SIGNATURE.len() + index * FIELD_SIZE
+    }
+
+    /// Human-readable name of the field
+    pub fn name(self) -> &'static str {
+        match self {
+            MetadataField::AbiType => "ABI Type",
+            MetadataField::ExtensionVersion => "Extension Version",
+            MetadataField::DuckdbVersion => "DuckDB Version",
+            MetadataField::DuckdbPlatform => "DuckDB Platform",
+        }
+    }
+}
+
+/// Typed representation of a DuckDB extension metadata footer
+///
+/// Text fields, including the reserved ones, are stored up to their first
+/// null byte and decoded as lossy UTF-8, so fields holding other bytes are
+/// not written back unchanged. The signature area is kept verbatim.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionMetadata {
+    /// Three reserved fields preceding the ABI type
+    pub reserved: [String; 3],
+    /// ABI type specification (e.g. "C_STRUCT_UNSTABLE")
+    pub abi_type: String,
+    /// Version string of the extension (e.g. "v1.0.0")
+    pub extension_version: String,
+    /// DuckDB version compatibility (e.g. "v1.4.2")
+    pub duckdb_version: String,
+    /// Target platform identifier (e.g. "linux_amd64")
+    pub duckdb_platform: String,
+    /// Metadata format version, always "4" in current DuckDB format
+    pub metadata_version: String,
+    /// 256-byte signature area, all zeros for unsigned extensions
+    pub signature: [u8; SIGNATURE_SIZE],
+}
+
+impl ExtensionMetadata {
+    /// Creates unsigned metadata with empty reserved fields
+    pub fn new(
+        abi_type: impl Into<String>,
+        extension_version: impl Into<String>,
+        duckdb_version: impl Into<String>,
+        duckdb_platform: impl Into<String>,
+    ) -> Self {
+        Self {
+            reserved: Default::default(),
+            abi_type: abi_type.into(),
+            extension_version: extension_version.into(),
+            duckdb_version: duckdb_version.into(),
+            duckdb_platform: duckdb_platform.into(),
+            metadata_version: METADATA_VERSION.to_owned(),
+            signature: [0u8; SIGNATURE_SIZE],
+        }
+    }
+
+    /// Returns `true` if the signature area contains any non-zero byte
+    pub fn is_signed(&self) -> bool {
+        self.signature.iter().any(|&byte| byte != 0)
+    }
+
+    /// Encodes the metadata into the 534-byte footer
+    ///
+    /// Fails if any text field is longer than 32 bytes.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, FooterError> {
+        let mut bytes = Vec::with_capacity(FOOTER_SIZE);
+        bytes.extend_from_slice(&START_SIGNATURE);
+        for field in self.fields() {
+            bytes.extend_from_slice(&encode_field(field)?);
+        }
+        bytes.extend_from_slice(&self.signature);
+        Ok(bytes)
+    }
+
+    /// Decodes the metadata from the trailing 534 bytes of the given buffer
+    ///
+    /// The buffer may be the footer alone or a complete extension file.
+    pub fn from_footer(bytes: &[u8]) -> Result<Self, FooterError> {
+        let footer = bytes
+            .len()
+            .checked_sub(FOOTER_SIZE)
+            .map(|start| &bytes[start..])
+            .ok_or(FooterError::Truncated(bytes.len()))?;
+        let (start_signature, rest) = footer.split_at(START_SIGNATURE.len());
+        if start_signature != START_SIGNATURE {
+            return Err(FooterError::MissingSignature);
+        }
+
+        let (fields, signature) = rest.split_at(8 * FIELD_SIZE);
+        let mut fields = fields.chunks_exact(FIELD_SIZE).map(decode_field);
+        let mut next_field = || fields.next().unwrap_or_default();
+        Ok(Self {
+            reserved: [next_field(), next_field(), next_field()],
+            abi_type: next_field(),
+            extension_version: next_field(),
+            duckdb_version: next_field(),
+            duckdb_platform: next_field(),
+            metadata_version: next_field(),
+            signature: signature.try_into().expect("signature area has a fixed size"),
+        })
+    }
+
+    /// Reads and decodes the footer at the end of a seekable stream
+    pub fn read_from(mut reader: impl Read + Seek) -> Result<Self, FooterError> {
+        let length = reader.seek(SeekFrom::End(0))?;
+        if length < FOOTER_SIZE as u64 {
+            return Err(FooterError::Truncated(length as usize));
+        }
+        reader.seek(SeekFrom::Start(length - FOOTER_SIZE as u64))?;
+        let mut bytes = vec![0u8; FOOTER_SIZE];
+        reader.read_exact(&mut bytes)?;
+        Self::from_footer(&bytes)
+    }
+
+    /// Encodes the metadata and appends the footer to the given writer
+    pub fn append_to(&self, mut writer: impl Write) -> Result<(), FooterError> {
+        writer.write_all(&self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Returns the value of a single text field
+    pub fn field(&self, field: MetadataField) -> &str {
+        match field {
+            MetadataField::AbiType => &self.abi_type,
+            MetadataField::ExtensionVersion => &self.extension_version,
+            MetadataField::DuckdbVersion => &self.duckdb_version,
+            MetadataField::DuckdbPlatform => &self.duckdb_platform,
+        }
+    }
+
+    /// Overwrites a single field of the footer at the end of a seekable stream
+    ///
+    /// Only the 32 bytes of the given field are written; the rest of the
+    /// stream is left untouched. The caller is expected to have verified
+    /// that the stream ends with a footer, e.g. with [`ExtensionMetadata::read_from`].
+    pub fn rewrite_field(mut writer: impl Write + Seek, field: MetadataField, value: &str) -> Result<(), FooterError> {
+        let bytes = encode_field(value)?;
+        let length = writer.seek(SeekFrom::End(0))?;
+        if length < FOOTER_SIZE as u64 {
+            return Err(FooterError::Truncated(length as usize));
+        }
+        writer.seek(SeekFrom::Start(length - FOOTER_SIZE as u64 + field.offset() as u64))?;
+        writer.write_all(&bytes)?;
+        Ok(())
+    }
+
+    /// Lists the eight text fields in footer order
+    fn fields(&self) -> [&str; 8] {
+        [
+            &self.reserved[0],
+            &self.reserved[1],
+            &self.reserved[2],
+            &self.abi_type,
+            &self.extension_version,
+            &self.duckdb_version,
+            &self.duckdb_platform,
+            &self.metadata_version,
+        ]
+    }
+}
+
+/// Checks whether a seekable stream ends with a metadata footer
+///
+/// Only the start signature at `len - 534` is inspected; the fields
+/// themselves are not decoded.
+pub fn has_footer(mut reader: impl Read + Seek) -> Result<bool, FooterError> {
+    let length = reader.seek(SeekFrom::End(0))?;
+    if length < FOOTER_SIZE as u64 {
+        return Ok(false);
+    }
+    reader.seek(SeekFrom::Start(length - FOOTER_SIZE as u64))?;
+    let mut start_signature = [0u8; START_SIGNATURE.len()];
+    reader.read_exact(&mut start_signature)?;
+    Ok(start_signature == START_SIGNATURE)
+}
+
+/// Pads the content to exactly 32 bytes with null bytes
+fn encode_field(content: &str) -> Result<[u8; FIELD_SIZE], FooterError> {
+    if content.len() > FIELD_SIZE {
+        return Err(FooterError::FieldTooLong(content.to_owned()));
+    }
+    let mut bytes = [0u8; FIELD_SIZE];
+    bytes[..content.len()].copy_from_slice(content.as_bytes());
+    Ok(bytes)
+}
+
+/// Decodes a 32-byte field by stripping the null padding
+fn decode_field(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn metadata() -> ExtensionMetadata {
+        ExtensionMetadata::new("C_STRUCT_UNSTABLE", "v1.0.0", "v1.4.2", "linux_amd64")
+    }
+
+    #[test]
+    fn footer_round_trip() {
+        let mut metadata = metadata();
+        metadata.reserved[1] = "reserved".to_owned();
+        metadata.signature[0] = 1;
+        let bytes = metadata.to_bytes().unwrap();
+        assert_eq!(bytes.len(), FOOTER_SIZE);
+        assert_eq!(&bytes[..START_SIGNATURE.len()], START_SIGNATURE);
+        assert_eq!(ExtensionMetadata::from_footer(&bytes).unwrap(), metadata);
+    }
+
+    #[test]
+    fn footer_decoded_from_end_of_file() {
+        let mut file = b"library".to_vec();
+        metadata().append_to(&mut file).unwrap();
+        assert_eq!(file.len(), 7 + FOOTER_SIZE);
+        assert_eq!(ExtensionMetadata::from_footer(&file).unwrap(), metadata());
+        assert_eq!(ExtensionMetadata::read_from(Cursor::new(&file)).unwrap(), metadata());
+        assert!(has_footer(Cursor::new(&file)).unwrap());
+        assert!(!has_footer(Cursor::new(b"library")).unwrap());
+    }
+
+    #[test]
+    fn rewrite_field_only_touches_its_field() {
+        let mut file = b"library".to_vec();
+        metadata().append_to(&mut file).unwrap();
+        let original = file.clone();
+        let mut cursor = Cursor::new(&mut file);
+        ExtensionMetadata::rewrite_field(&mut cursor, MetadataField::DuckdbPlatform, "osx_arm64").unwrap();
+
+        let start = original.len() - FOOTER_SIZE + MetadataField::DuckdbPlatform.offset();
+        assert_eq!(file[..start], original[..start]);
+        assert_eq!(file[start + FIELD_SIZE..], original[start + FIELD_SIZE..]);
+        let decoded = ExtensionMetadata::read_from(Cursor::new(&file)).unwrap();
+        assert_eq!(decoded.duckdb_platform, "osx_arm64");
+        assert_eq!(decoded.abi_type, "C_STRUCT_UNSTABLE");
+    }
+
+    #[test]
+    fn field_offsets() {
+        assert_eq!(MetadataField::AbiType.offset(), 22 + 3 * 32);
+        assert_eq!(MetadataField::ExtensionVersion.offset(), 22 + 4 * 32);
+        assert_eq!(MetadataField::DuckdbVersion.offset(), 22 + 5 * 32);
+        assert_eq!(MetadataField::DuckdbPlatform.offset(), 22 + 6 * 32);
+        let bytes = metadata().to_bytes().unwrap();
+        let offset = MetadataField::ExtensionVersion.offset();
+        assert_eq!(&bytes[offset..offset + 6], b"v1.0.0");
+    }
+
+    #[test]
+    fn truncated_input() {
+        let error = ExtensionMetadata::from_footer(&[0u8; 100]).unwrap_err();
+        assert!(matches!(error, FooterError::Truncated(100)));
+        let error = ExtensionMetadata::read_from(Cursor::new(vec![0u8; 10])).unwrap_err();
+        assert!(matches!(error, FooterError::Truncated(10)));
+        let error = ExtensionMetadata::rewrite_field(Cursor::new(Vec::new()), MetadataField::AbiType, "CPP").unwrap_err();
+        assert!(matches!(error, FooterError::Truncated(0)));
+    }
+
+    #[test]
+    fn missing_signature() {
+        let mut bytes = metadata().to_bytes().unwrap();
+        bytes[1] = 0;
+        let error = ExtensionMetadata::from_footer(&bytes).unwrap_err();
+        assert!(matches!(error, FooterError::MissingSignature));
+    }
+
+    #[test]
+    fn field_too_long() {
+        let platform = "x".repeat(FIELD_SIZE + 1);
+        let error = ExtensionMetadata::new("CPP", "v1.0.0", "v1.4.2", platform.as_str()).to_bytes().unwrap_err();
+        assert!(matches!(error, FooterError::FieldTooLong(value) if value == platform));
+        let exact = "x".repeat(FIELD_SIZE);
+        assert!(ExtensionMetadata::new("CPP", "v1.0.0", "v1.4.2", exact.as_str()).to_bytes().is_ok());
+    }
+}
@@ -5,13 +5,27 @@
 //! It handles the 534-byte footer structure that contains version, platform,
 //! and compatibility information.
 
+use crate::abi::AbiType;
+use crate::architecture::verify_architecture;
+use crate::compression::Compression;
 use crate::console;
 use crate::error::ToolsError;
+use crate::exports::extension_name;
+use crate::exports::verify_entrypoint;
 use crate::fs::open_duplicate;
+use crate::fs::strip_footer;
 use crate::logger::QUITE;
+use crate::logger::set_quiet;
+use crate::platform::Platform;
+use crate::platform::PlatformParser;
+use crate::repository::repository_path;
+use crate::validation::Validator;
+use cargo_duckdb_ext_tools::has_footer;
+use cargo_duckdb_ext_tools::ExtensionMetadata;
+use clap::builder::BoolishValueParser;
 use clap::Parser;
+use std::fs::remove_file;
 use std::fs::File;
-use std::io::Write;
 
 /// Command line options for the `duckdb-ext-pack` subcommand
 ///
@@ -21,31 +35,59 @@ use std::io::Write;
 #[command(name = "duckdb-ext-pack", version, author, about = "", long_about = "")]
 pub(super) struct PackerOptions {
     /// Path to the input dynamic library file
-    #[arg(short = 'i', long, value_name = "LIBRARY-PATH")]
+    #[arg(short = 'i', long, value_name = "LIBRARY-PATH", env = "DUCKDB_EXT_LIBRARY_PATH")]
     library_path: String,
 
     /// Path where the output extension file should be created
-    #[arg(short = 'o', long, value_name = "EXTENSION-PATH")]
+    #[arg(short = 'o', long, value_name = "EXTENSION-PATH", env = "DUCKDB_EXT_PATH")]
     extension_path: String,
 
+    /// Extension repository to write the extension to, named after the file name of --extension-path
+    #[arg(long, value_name = "REPOSITORY", env = "DUCKDB_EXT_REPOSITORY")]
+    repository: Option<String>,
+
     /// Version of the extension (e.g., "v1.0.0")
-    #[arg(short = 'v', long, value_name = "EXTENSION-VERSION")]
+    #[arg(short = 'v', long, value_name = "EXTENSION-VERSION", env = "DUCKDB_EXT_VERSION")]
     extension_version: String,
 
     /// Target platform identifier (e.g., "osx_arm64", "linux_amd64")
-    #[arg(short = 'p', long, value_name = "DUCKDB-PLATFORM")]
-    duckdb_platform: String,
+    #[arg(short = 'p', long, value_name = "DUCKDB-PLATFORM", value_parser = PlatformParser, env = "DUCKDB_PLATFORM")]
+    duckdb_platform: Platform,
 
     /// DuckDB version the extension is built for (e.g., "v1.4.2")
-    #[arg(short = 'd', long, value_name = "DUCKDB-VERSION")]
+    #[arg(short = 'd', long, value_name = "DUCKDB-VERSION", env = "DUCKDB_VERSION")]
     duckdb_version: String,
 
+    /// DuckDB release naming the --repository directory (defaults to --duckdb-version, required for C_STRUCT)
+    #[arg(long, value_name = "DUCKDB-RELEASE", env = "DUCKDB_RELEASE")]
+    duckdb_release: Option<String>,
+
     /// ABI type for the extension (defaults to "C_STRUCT_UNSTABLE")
-    #[arg(short = 'a', long, value_name = "ABI-TYPE", default_value = "C_STRUCT_UNSTABLE")]
-    abi_type: String,
+    #[arg(short = 'a', long, value_name = "ABI-TYPE", value_enum, default_value_t = AbiType::CStructUnstable, env = "DUCKDB_ABI_TYPE")]
+    abi_type: AbiType,
+
+    /// Skip checking that the library exports the entry point DuckDB will call
+    #[arg(long, default_value_t = false, env = "DUCKDB_EXT_NO_VERIFY_SYMBOLS", value_parser = BoolishValueParser::new())]
+    no_verify_symbols: bool,
+
+    /// Pack even if the library was built for a different platform than declared
+    #[arg(short = 'f', long, default_value_t = false, env = "DUCKDB_EXT_FORCE", value_parser = BoolishValueParser::new())]
+    force: bool,
+
+    /// Compress the extension, replacing the plain file unless --keep-uncompressed is given
+    #[arg(long, value_name = "FORMAT", value_enum, env = "DUCKDB_EXT_COMPRESS")]
+    compress: Option<Compression>,
+
+    /// Keep the uncompressed extension next to the compressed one
+    #[arg(long, default_value_t = false, env = "DUCKDB_EXT_KEEP_UNCOMPRESSED", value_parser = BoolishValueParser::new())]
+    keep_uncompressed: bool,
+
+    /// Replace the footer if the input is already a DuckDB extension
+    #[arg(short = 'r', long, default_value_t = false, env = "DUCKDB_EXT_REPLACE", value_parser = BoolishValueParser::new())]
+    replace: bool,
 
     /// Suppress console output
-    #[arg(short = 'q', long, default_value_t = false)]
+    #[arg(short = 'q', long, default_value_t = false, env = "DUCKDB_EXT_QUIET", value_parser = BoolishValueParser::new())]
     quiet: bool,
 }
 
@@ -57,14 +99,20 @@ pub(super) struct PackerOptions {
 pub(super) struct Packer {
     /// File handle opened in append mode for writing metadata
     pub(super) file: File,
+    /// Path of the extension file being written
+    pub(super) extension_path: String,
     /// Version string of the extension (must start with 'v')
     pub(super) extension_version: String,
     /// Target platform identifier
-    pub(super) duckdb_platform: String,
+    pub(super) duckdb_platform: Platform,
     /// DuckDB version compatibility
     pub(super) duckdb_version: String,
     /// ABI type specification
-    pub(super) abi_type: String,
+    pub(super) abi_type: AbiType,
+    /// Compression applied after the footer is written
+    pub(super) compression: Option<Compression>,
+    /// Whether to keep the plain file next to the compressed one
+    pub(super) keep_uncompressed: bool,
 }
 
 impl TryFrom<PackerOptions> for Packer {
@@ -72,17 +120,62 @@ impl TryFrom<PackerOptions> for Packer {
 
     /// Constructs a Packer from command line options
     ///
-    /// This conversion sets up the global quiet flag and creates the
-    /// extension file by duplicating the source library.
+    /// This conversion sets up the global quiet flag, validates every
+    /// metadata value, verifies the exported entry point and the target
+    /// platform, and creates the extension file by duplicating the source
+    /// library, inside the extension repository if one is given. If the library
+    /// already ends with a footer, it is either rejected or, with `--replace`,
+    /// stripped from the copy so that exactly one footer is written. When the
+    /// extension path is the library itself, the footer is replaced in place.
     fn try_from(parameters: PackerOptions) -> Result<Self, Self::Error> {
-        QUITE.set(parameters.quiet).expect("Failed to set quiet");
-        let file = open_duplicate(&parameters.library_path, &parameters.extension_path)?;
+        set_quiet(parameters.quiet)?;
+        Validator::default()
+            .extension_version(Some(&parameters.extension_version))
+            .duckdb_version(parameters.abi_type, Some(&parameters.duckdb_version))
+            .duckdb_release(parameters.duckdb_release.as_deref())
+            .duckdb_platform(Some(&parameters.duckdb_platform))
+            .finish()?;
+        let extension_name = extension_name(&parameters.extension_path);
+        let extension_path = match parameters.repository.as_ref() {
+            Some(repository) => {
+                // The DuckDB version of a C_STRUCT extension is a C API version, not the release
+                let duckdb_release = match (parameters.duckdb_release.as_ref(), parameters.abi_type) {
+                    (Some(duckdb_release), _) => duckdb_release,
+                    (None, AbiType::CStructUnstable | AbiType::Cpp) => &parameters.duckdb_version,
+                    (None, AbiType::CStruct) => {
+                        return Err(ToolsError::MissingDuckdbRelease {
+                            extension: extension_name,
+                            hint: "pass it with --duckdb-release",
+                        });
+                    }
+                };
+                repository_path(repository, duckdb_release, &parameters.duckdb_platform, &extension_name)
+            }
+            None => parameters.extension_path,
+        };
+        if !parameters.no_verify_symbols {
+            verify_entrypoint(&parameters.library_path, &extension_name, parameters.abi_type)?;
+        }
+        if !parameters.force {
+            verify_architecture(&parameters.library_path, &parameters.duckdb_platform)?;
+        }
+        let packed = has_footer(File::open(&parameters.library_path)?)?;
+        if packed && !parameters.replace {
+            return Err(ToolsError::FooterAlreadyPresent(parameters.library_path));
+        }
+        let file = open_duplicate(&parameters.library_path, &extension_path)?;
+        if packed {
+            strip_footer(&file)?;
+        }
         Ok(Self {
             file,
+            extension_path,
             extension_version: parameters.extension_version,
             duckdb_platform: parameters.duckdb_platform,
             duckdb_version: parameters.duckdb_version,
             abi_type: parameters.abi_type,
+            compression: parameters.compress,
+            keep_uncompressed: parameters.keep_uncompressed,
         })
     }
 }
@@ -90,57 +183,67 @@ impl TryFrom<PackerOptions> for Packer {
 impl Packer {
     /// Appends the 534-byte DuckDB extension metadata footer to the file
     ///
-    /// This method writes the complete metadata structure including:
-    /// - Start signature
-    /// - Reserved fields
-    /// - ABI type
-    /// - Extension version
-    /// - DuckDB version
-    /// - Platform identifier
-    /// - Unknown field (always "4")
-    /// - Signature padding
+    /// The footer layout is implemented by [`ExtensionMetadata`]; this method
+    /// reports each packed value and appends the encoded footer. With a
+    /// compression, the extension is then compressed next to itself and the
+    /// extension path points to the compressed file.
     pub(super) fn write_metadata(&mut self) -> Result<(), ToolsError> {
-        Self::write_start_signature(&mut self.file)?;
-        // Write 3 empty 32-byte fields (reserved for future use)
-        for _ in 0..3 {
-            Self::write_field(&mut self.file, "")?;
-        }
         console!("     Packing ABI Type ({})", self.abi_type);
-        Self::write_field(&mut self.file, self.abi_type.as_str())?;
         console!("     Packing Extension Version ({})", self.extension_version);
-        Self::write_field(&mut self.file, self.extension_version.as_str())?;
         console!("     Packing DuckDB Version ({})", self.duckdb_version);
-        Self::write_field(&mut self.file, self.duckdb_version.as_str())?;
         console!("     Packing DuckDB Platform ({})", self.duckdb_platform);
-        Self::write_field(&mut self.file, self.duckdb_platform.as_str())?;
-        // Unknown field, always "4" in current DuckDB format
-        Self::write_field(&mut self.file, "4")?;
-        // Write 8 empty 32-byte fields for signature padding
-        for _ in 0..8 {
-            Self::write_field(&mut self.file, "")?;
+        ExtensionMetadata::new(
+            self.abi_type.as_str(),
+            self.extension_version.as_str(),
+            self.duckdb_version.as_str(),
+            self.duckdb_platform.as_str(),
+        )
+        .append_to(&mut self.file)?;
+        if let Some(compression) = self.compression {
+            let compressed_path = format!("{}.{}", self.extension_path, compression.extension());
+            console!(" Compressing Extension File ({compressed_path})");
+            compression.compress(File::open(&self.extension_path)?, File::create(&compressed_path)?)?;
+            if !self.keep_uncompressed {
+                console!("    Removing Extension File ({})", self.extension_path);
+                remove_file(&self.extension_path)?;
+            }
+            self.extension_path = compressed_path;
         }
         console!("    Finished DuckDB Extension");
         Ok(())
     }
+}
 
-    /// Writes the fixed start signature for DuckDB extension files
-    ///
-    /// The signature is a specific byte sequence that identifies the file
-    /// as a DuckDB extension. This is the first part of the 534-byte footer.
-    fn write_start_signature(file: &mut File) -> Result<(), std::io::Error> {
-        file.write_all(&vec![
-            0, 147, 4, 16, 100, 117, 99, 107, 100, 98, 95, 115, 105, 103, 110, 97, 116, 117, 114,
-            101, 128, 4,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cargo_duckdb_ext_tools::FOOTER_SIZE;
+
+    fn pack_in_place(path: &str) -> Result<(), ToolsError> {
+        let parameters = PackerOptions::try_parse_from([
+            "duckdb-ext-pack", "-i", path, "-o", path, "-v", "v1.0.0", "-p", "linux_amd64", "-d", "v1.4.2",
+            "--no-verify-symbols", "--force", "--replace", "-q",
         ])
+        .unwrap();
+        Packer::try_from(parameters)?.write_metadata()
     }
 
-    /// Writes a 32-byte field with the given content
-    ///
-    /// This pads the content to exactly 32 bytes with null bytes.
-    /// If the content is longer than 32 bytes, it will be truncated.
-    fn write_field(file: &mut File, content: &str) -> Result<(), std::io::Error> {
-        let mut bytes = [0u8; 32];
-        bytes[..content.len()].copy_from_slice(content.as_bytes());
-        file.write_all(bytes.as_ref())
+    #[test]
+    fn replaces_footer_in_place() {
+        let path = std::env::temp_dir().join(format!("quack-{}.duckdb_extension", std::process::id()));
+        let path = path.to_str().unwrap();
+        let library = vec![0x7fu8; 4096];
+        std::fs::write(path, &library).unwrap();
+
+        pack_in_place(path).unwrap();
+        assert_eq!(std::fs::metadata(path).unwrap().len(), (library.len() + FOOTER_SIZE) as u64);
+        pack_in_place(path).unwrap();
+        let packed = std::fs::read(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(packed.len(), library.len() + FOOTER_SIZE);
+        assert_eq!(packed[..library.len()], library);
+        let metadata = ExtensionMetadata::from_footer(&packed).unwrap();
+        assert_eq!(metadata.duckdb_platform, "linux_amd64");
     }
 }
@@ -0,0 +1,287 @@
+//! DuckDB platform identifiers
+//!
+//! This module models the platforms DuckDB publishes extensions for, with
+//! an escape hatch for identifiers this tool does not know about yet.
+
+use crate::error::ToolsError;
+use clap::builder::PossibleValue;
+use clap::builder::TypedValueParser;
+use clap::error::ErrorKind;
+use clap::ValueEnum;
+use std::ffi::OsStr;
+use std::fmt;
+use target_lexicon::Architecture;
+use target_lexicon::Environment;
+use target_lexicon::OperatingSystem;
+use target_lexicon::Triple;
+
+/// Platforms known to this tool, in the order they are listed in `--help`
+static KNOWN_PLATFORMS: [Platform; 16] = [
+    Platform::LinuxAmd64,
+    Platform::LinuxAmd64Gcc4,
+    Platform::LinuxAmd64Musl,
+    Platform::LinuxArm64,
+    Platform::LinuxArm64Gcc4,
+    Platform::LinuxArm64Musl,
+    Platform::OsxAmd64,
+    Platform::OsxArm64,
+    Platform::WindowsAmd64,
+    Platform::WindowsAmd64Mingw,
+    Platform::WindowsAmd64Rtools,
+    Platform::WindowsArm64,
+    Platform::FreebsdAmd64,
+    Platform::WasmMvp,
+    Platform::WasmEh,
+    Platform::WasmThreads,
+];
+
+/// DuckDB platform identifier recorded in the extension footer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum Platform {
+    LinuxAmd64,
+    LinuxAmd64Gcc4,
+    LinuxAmd64Musl,
+    LinuxArm64,
+    LinuxArm64Gcc4,
+    LinuxArm64Musl,
+    OsxAmd64,
+    OsxArm64,
+    WindowsAmd64,
+    WindowsAmd64Mingw,
+    WindowsAmd64Rtools,
+    WindowsArm64,
+    FreebsdAmd64,
+    WasmMvp,
+    WasmEh,
+    WasmThreads,
+    /// Any other identifier, passed through verbatim
+    Other(String),
+}
+
+impl Platform {
+    /// Returns the identifier of a known platform
+    fn name(&self) -> Option<&'static str> {
+        let name = match self {
+            Platform::LinuxAmd64 => "linux_amd64",
+            Platform::LinuxAmd64Gcc4 => "linux_amd64_gcc4",
+            Platform::LinuxAmd64Musl => "linux_amd64_musl",
+            Platform::LinuxArm64 => "linux_arm64",
+            Platform::LinuxArm64Gcc4 => "linux_arm64_gcc4",
+            Platform::LinuxArm64Musl => "linux_arm64_musl",
+            Platform::OsxAmd64 => "osx_amd64",
+            Platform::OsxArm64 => "osx_arm64",
+            Platform::WindowsAmd64 => "windows_amd64",
+            Platform::WindowsAmd64Mingw => "windows_amd64_mingw",
+            Platform::WindowsAmd64Rtools => "windows_amd64_rtools",
+            Platform::WindowsArm64 => "windows_arm64",
+            Platform::FreebsdAmd64 => "freebsd_amd64",
+            Platform::WasmMvp => "wasm_mvp",
+            Platform::WasmEh => "wasm_eh",
+            Platform::WasmThreads => "wasm_threads",
+            Platform::Other(_) => return None,
+        };
+        Some(name)
+    }
+
+    /// Returns the identifier written into the footer
+    pub(super) fn as_str(&self) -> &str {
+        match self {
+            Platform::Other(name) => name,
+            platform => platform.name().unwrap_or_default(),
+        }
+    }
+
+    /// Returns the operating system part of the identifier (e.g. "linux")
+    pub(super) fn os(&self) -> &str {
+        self.as_str().split('_').next().unwrap_or_default()
+    }
+
+    /// Returns the architecture part of the identifier (e.g. "amd64")
+    ///
+    /// WebAssembly platforms name a feature set instead of an architecture,
+    /// so `None` is returned for them.
+    pub(super) fn arch(&self) -> Option<&str> {
+        match self.os() {
+            "wasm" => None,
+            _ => self.as_str().split('_').nth(1),
+        }
+    }
+
+    /// Returns `true` if the identifier is not one of the known platforms
+    pub(super) fn is_other(&self) -> bool {
+        matches!(self, Platform::Other(_))
+    }
+
+    /// Returns `true` if the identifier looks like `<os>_<arch>[_<variant>]`
+    pub(super) fn is_well_formed(&self) -> bool {
+        let parts = self.as_str().split('_');
+        parts.clone().count() >= 2
+            && parts.into_iter().all(|part| {
+                !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit())
+            })
+    }
+
+    /// Maps a Rust target triple to the DuckDB platform it builds for
+    ///
+    /// The environment selects the libc or toolchain variant, e.g. `musl`
+    /// targets map to `linux_*_musl` and `windows-gnu` to `windows_amd64_mingw`.
+    /// Variants that cannot be told apart by the triple (`linux_*_gcc4` for
+    /// DuckDB releases before v1.3, `windows_amd64_rtools`, `wasm_eh` and
+    /// `wasm_threads`) have to be selected explicitly. Fails for targets DuckDB
+    /// does not publish extensions for; DuckDB-Wasm only loads extensions
+    /// built with Emscripten, so other wasm targets are rejected too.
+    pub(super) fn from_triple(triple: &Triple) -> Result<Platform, ToolsError> {
+        let unsupported = |component| ToolsError::UnsupportedTarget {
+            triple: triple.to_string(),
+            component,
+        };
+        let platform = match (triple.operating_system, triple.architecture, triple.environment) {
+            (OperatingSystem::Linux, Architecture::X86_64, Environment::Musl) => Platform::LinuxAmd64Musl,
+            (OperatingSystem::Linux, Architecture::X86_64, Environment::Gnu) => Platform::LinuxAmd64,
+            (OperatingSystem::Linux, Architecture::Aarch64(_), Environment::Musl) => Platform::LinuxArm64Musl,
+            (OperatingSystem::Linux, Architecture::Aarch64(_), Environment::Gnu) => Platform::LinuxArm64,
+            (OperatingSystem::Darwin(_) | OperatingSystem::MacOSX(_), Architecture::X86_64, _) => Platform::OsxAmd64,
+            (OperatingSystem::Darwin(_) | OperatingSystem::MacOSX(_), Architecture::Aarch64(_), _) => Platform::OsxArm64,
+            (OperatingSystem::Windows, Architecture::X86_64, Environment::Msvc) => Platform::WindowsAmd64,
+            (OperatingSystem::Windows, Architecture::X86_64, Environment::Gnu | Environment::GnuLlvm) => {
+                Platform::WindowsAmd64Mingw
+            }
+            (OperatingSystem::Windows, Architecture::Aarch64(_), Environment::Msvc) => Platform::WindowsArm64,
+            (OperatingSystem::Freebsd, Architecture::X86_64, _) => Platform::FreebsdAmd64,
+            (OperatingSystem::Emscripten, Architecture::Wasm32, _) => Platform::WasmMvp,
+            (OperatingSystem::Linux | OperatingSystem::Windows, Architecture::X86_64 | Architecture::Aarch64(_), _) => {
+                return Err(unsupported("environment"));
+            }
+            (
+                OperatingSystem::Linux
+                | OperatingSystem::Darwin(_)
+                | OperatingSystem::MacOSX(_)
+                | OperatingSystem::Windows
+                | OperatingSystem::Freebsd,
+                _,
+                _,
+            ) => return Err(unsupported("architecture")),
+            _ => return Err(unsupported("operating system")),
+        };
+        Ok(platform)
+    }
+
+    /// Lists the identifiers of all known platforms
+    pub(super) fn known_names() -> impl Iterator<Item = &'static str> {
+        KNOWN_PLATFORMS.iter().filter_map(Platform::name)
+    }
+}
+
+impl From<&str> for Platform {
+    /// Parses an identifier, falling back to `Platform::Other` for unknown ones
+    fn from(name: &str) -> Self {
+        KNOWN_PLATFORMS
+            .iter()
+            .find(|platform| platform.name() == Some(name))
+            .cloned()
+            .unwrap_or_else(|| Platform::Other(name.to_owned()))
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ValueEnum for Platform {
+    fn value_variants<'a>() -> &'a [Self] {
+        &KNOWN_PLATFORMS
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        self.name().map(PossibleValue::new)
+    }
+}
+
+/// Command line parser for `Platform`
+///
+/// Unlike clap's `EnumValueParser`, this parser accepts identifiers that are
+/// not listed in `--help` and maps them to `Platform::Other`.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct PlatformParser;
+
+impl TypedValueParser for PlatformParser {
+    type Value = Platform;
+
+    fn parse_ref(
+        &self,
+        command: &clap::Command,
+        _argument: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        value
+            .to_str()
+            .map(Platform::from)
+            .ok_or_else(|| clap::Error::new(ErrorKind::InvalidUtf8).with_cmd(command))
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            Platform::value_variants()
+                .iter()
+                .filter_map(ValueEnum::to_possible_value),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn from_triple(triple: &str) -> Result<Platform, ToolsError> {
+        Platform::from_triple(&Triple::from_str(triple).unwrap())
+    }
+
+    #[test]
+    fn maps_supported_triples() {
+        let mappings = [
+            ("x86_64-apple-darwin", Platform::OsxAmd64),
+            ("aarch64-apple-darwin", Platform::OsxArm64),
+            ("x86_64-unknown-linux-gnu", Platform::LinuxAmd64),
+            ("aarch64-unknown-linux-gnu", Platform::LinuxArm64),
+            ("x86_64-pc-windows-msvc", Platform::WindowsAmd64),
+            ("x86_64-unknown-linux-musl", Platform::LinuxAmd64Musl),
+            ("aarch64-unknown-linux-musl", Platform::LinuxArm64Musl),
+            ("x86_64-pc-windows-gnu", Platform::WindowsAmd64Mingw),
+            ("x86_64-pc-windows-gnullvm", Platform::WindowsAmd64Mingw),
+            ("aarch64-pc-windows-msvc", Platform::WindowsArm64),
+            ("x86_64-unknown-freebsd", Platform::FreebsdAmd64),
+            ("wasm32-unknown-emscripten", Platform::WasmMvp),
+        ];
+        for (triple, platform) in mappings {
+            assert_eq!(from_triple(triple).unwrap(), platform, "{triple}");
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_triples() {
+        let unsupported = [
+            ("x86_64-unknown-linux-gnux32", "environment"),
+            ("aarch64-pc-windows-gnullvm", "environment"),
+            ("i686-unknown-linux-gnu", "architecture"),
+            ("riscv64gc-unknown-linux-gnu", "architecture"),
+            ("i686-pc-windows-msvc", "architecture"),
+            ("x86_64-unknown-netbsd", "operating system"),
+            ("aarch64-linux-android", "environment"),
+            ("aarch64-apple-ios", "operating system"),
+            ("wasm32-unknown-unknown", "operating system"),
+            ("wasm32-wasip1-threads", "operating system"),
+        ];
+        for (triple, expected) in unsupported {
+            match from_triple(triple) {
+                Err(ToolsError::UnsupportedTarget { triple: reported, component }) => {
+                    assert_eq!(reported, triple);
+                    assert_eq!(component, expected, "{triple}");
+                }
+                result => panic!("{triple} mapped to {result:?}"),
+            }
+        }
+    }
+}
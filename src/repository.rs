@@ -0,0 +1,20 @@
+//! Extension repository layout
+//!
+//! `INSTALL <name> FROM '<repository>'` looks extensions up under
+//! `<repository>/<duckdb_version>/<platform>/<name>.duckdb_extension`, where
+//! the version is the DuckDB release, also for C_STRUCT extensions that
+//! record a C API version in their footer.
+//! Writing packed extensions into that layout lets a repository directory
+//! be served or synced as is.
+
+use crate::platform::Platform;
+use cargo_metadata::camino::Utf8Path;
+
+/// Returns the path of an extension inside a repository directory
+pub(super) fn repository_path(repository: &str, duckdb_release: &str, duckdb_platform: &Platform, extension_name: &str) -> String {
+    Utf8Path::new(repository)
+        .join(duckdb_release)
+        .join(duckdb_platform.as_str())
+        .join(format!("{extension_name}.duckdb_extension"))
+        .to_string()
+}
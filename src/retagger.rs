@@ -0,0 +1,131 @@
+//! In-place DuckDB extension metadata rewriter
+//!
+//! This module implements the `duckdb-ext-retag` subcommand, which overwrites
+//! individual 32-byte fields of an existing extension footer without copying
+//! or otherwise modifying the rest of the file.
+
+use crate::abi::AbiType;
+use crate::console;
+use crate::error::ToolsError;
+use crate::inspector::read_metadata;
+use crate::logger::QUITE;
+use crate::logger::set_quiet;
+use crate::platform::Platform;
+use crate::platform::PlatformParser;
+use crate::validation::Validator;
+use cargo_duckdb_ext_tools::ExtensionMetadata;
+use cargo_duckdb_ext_tools::MetadataField;
+use clap::ArgGroup;
+use clap::Parser;
+use std::fs::OpenOptions;
+
+/// Command line options for the `duckdb-ext-retag` subcommand
+///
+/// This struct defines the fields that can be rewritten in the footer
+/// of an existing DuckDB extension file. At least one is required.
+#[derive(Parser, Debug)]
+#[command(name = "duckdb-ext-retag", version, author, about = "", long_about = "")]
+#[command(group(ArgGroup::new("fields").required(true).multiple(true)))]
+pub(super) struct RetaggerOptions {
+    /// Path to the extension file to rewrite in place
+    #[arg(short = 'i', long, value_name = "EXTENSION-PATH")]
+    extension_path: String,
+
+    /// New extension version (e.g., "v1.0.0")
+    #[arg(short = 'v', long, value_name = "EXTENSION-VERSION", group = "fields")]
+    extension_version: Option<String>,
+
+    /// New target platform identifier (e.g., "linux_amd64_gcc4")
+    #[arg(short = 'p', long, value_name = "DUCKDB-PLATFORM", group = "fields", value_parser = PlatformParser)]
+    duckdb_platform: Option<Platform>,
+
+    /// New DuckDB version (e.g., "v1.4.2")
+    #[arg(short = 'd', long, value_name = "DUCKDB-VERSION", group = "fields")]
+    duckdb_version: Option<String>,
+
+    /// New ABI type (e.g., "C_STRUCT")
+    #[arg(short = 'a', long, value_name = "ABI-TYPE", group = "fields", value_enum)]
+    abi_type: Option<AbiType>,
+
+    /// Suppress console output
+    #[arg(short = 'q', long, default_value_t = false)]
+    quiet: bool,
+}
+
+/// Rewrites selected metadata fields of an existing DuckDB extension
+pub(super) struct Retagger {
+    /// Path of the extension file being rewritten
+    extension_path: String,
+    /// Optional new extension version
+    extension_version: Option<String>,
+    /// Optional new target platform
+    duckdb_platform: Option<Platform>,
+    /// Optional new DuckDB version
+    duckdb_version: Option<String>,
+    /// Optional new ABI type
+    abi_type: Option<AbiType>,
+}
+
+impl TryFrom<RetaggerOptions> for Retagger {
+    type Error = ToolsError;
+
+    /// Constructs a Retagger from command line options
+    ///
+    /// This conversion sets up the global quiet flag.
+    fn try_from(parameters: RetaggerOptions) -> Result<Self, Self::Error> {
+        set_quiet(parameters.quiet)?;
+        Ok(Self {
+            extension_path: parameters.extension_path,
+            extension_version: parameters.extension_version,
+            duckdb_platform: parameters.duckdb_platform,
+            duckdb_version: parameters.duckdb_version,
+            abi_type: parameters.abi_type,
+        })
+    }
+}
+
+impl Retagger {
+    /// Overwrites the selected fields of the existing footer in place
+    ///
+    /// The new values are validated first. When the ABI type or the DuckDB
+    /// version changes, ABI rules are checked against the resulting
+    /// combination of old and new values; an empty ABI field counts as `CPP`.
+    /// Each changed field is then written at its offset and reported as
+    /// old vs. new value.
+    pub(super) fn retag(&self) -> Result<(), ToolsError> {
+        let metadata = read_metadata(&self.extension_path)?;
+        let mut validator = Validator::default()
+            .extension_version(self.extension_version.as_deref())
+            .duckdb_platform(self.duckdb_platform.as_ref());
+        if self.abi_type.is_some() || self.duckdb_version.is_some() {
+            let abi_type = match self.abi_type {
+                Some(abi_type) => abi_type,
+                None => AbiType::from_footer(&metadata.abi_type)
+                    .ok_or_else(|| ToolsError::UnknownAbiType(metadata.abi_type.to_owned()))?,
+            };
+            let duckdb_version = self.duckdb_version.as_deref().unwrap_or(&metadata.duckdb_version);
+            validator = validator.duckdb_version(abi_type, Some(duckdb_version));
+        }
+        validator.finish()?;
+
+        let fields = [
+            (MetadataField::AbiType, self.abi_type.map(|abi_type| abi_type.to_string())),
+            (MetadataField::ExtensionVersion, self.extension_version.to_owned()),
+            (MetadataField::DuckdbVersion, self.duckdb_version.to_owned()),
+            (MetadataField::DuckdbPlatform, self.duckdb_platform.as_ref().map(Platform::to_string)),
+        ];
+        let mut file = OpenOptions::new().read(true).write(true).open(&self.extension_path)?;
+        for (field, value) in fields {
+            let Some(value) = value else { continue };
+            let old_value = metadata.field(field);
+            if old_value == value {
+                console!("   Unchanged {} ({old_value})", field.name());
+            } else {
+                console!("   Retagging {} ({old_value} -> {value})", field.name());
+                ExtensionMetadata::rewrite_field(&mut file, field, &value)?;
+            }
+        }
+        console!("    Finished DuckDB Extension");
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+//! Local HTTP server for extension repositories
+//!
+//! This module implements the `duckdb-ext-serve` subcommand, which serves a
+//! directory in the extension repository layout over HTTP, so that
+//! `SET custom_extension_repository` and `INSTALL` can be tested without
+//! publishing the extensions.
+
+use crate::compression::Compression;
+use crate::console;
+use crate::error::ToolsError;
+use crate::logger::QUITE;
+use crate::logger::set_quiet;
+use clap::builder::BoolishValueParser;
+use clap::Parser;
+use std::collections::BTreeSet;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Command line options for the `duckdb-ext-serve` subcommand
+///
+/// This struct defines the parameters required to serve an extension
+/// repository directory on the local machine.
+#[derive(Parser, Debug)]
+#[command(name = "duckdb-ext-serve", version, author, about = "", long_about = "")]
+pub(super) struct ServerOptions {
+    /// Extension repository directory, laid out as <DUCKDB-VERSION>/<PLATFORM>/<NAME>.duckdb_extension
+    #[arg(value_name = "REPOSITORY", env = "DUCKDB_EXT_REPOSITORY")]
+    repository: String,
+
+    /// Address to listen on
+    #[arg(short = 'b', long, value_name = "ADDRESS", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    bind: IpAddr,
+
+    /// Port to listen on (0 picks a free port)
+    #[arg(short = 'P', long, value_name = "PORT", default_value_t = 8080)]
+    port: u16,
+
+    /// Compress uncompressed extensions on the fly when their `.gz` file is requested
+    #[arg(short = 'z', long, default_value_t = false, env = "DUCKDB_EXT_SERVE_GZIP", value_parser = BoolishValueParser::new())]
+    gzip: bool,
+
+    /// Suppress console output
+    #[arg(short = 'q', long, default_value_t = false, env = "DUCKDB_EXT_QUIET", value_parser = BoolishValueParser::new())]
+    quiet: bool,
+}
+
+/// Time a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serves an extension repository directory over HTTP
+pub(super) struct Server {
+    /// Root of the extension repository
+    repository: PathBuf,
+    /// Socket accepting connections
+    listener: TcpListener,
+    /// Whether to compress plain extension files on request
+    gzip: bool,
+}
+
+impl TryFrom<ServerOptions> for Server {
+    type Error = ToolsError;
+
+    /// Constructs a Server from command line options
+    ///
+    /// This sets up the global quiet flag, checks the repository directory
+    /// and binds the listening socket.
+    fn try_from(parameters: ServerOptions) -> Result<Self, Self::Error> {
+        set_quiet(parameters.quiet)?;
+        let repository = PathBuf::from(&parameters.repository);
+        if !repository.is_dir() {
+            return Err(ToolsError::RepositoryNotFound(parameters.repository));
+        }
+        Ok(Self {
+            repository,
+            listener: TcpListener::bind((parameters.bind, parameters.port))?,
+            gzip: parameters.gzip,
+        })
+    }
+}
+
+/// An extension file found in the repository
+struct RepositoryEntry {
+    /// Path relative to the repository root
+    path: String,
+    /// Extension name
+    name: String,
+    /// Whether the file is gzip-compressed
+    compressed: bool,
+}
+
+impl Server {
+    /// Prints the SQL to install the served extensions and serves requests
+    ///
+    /// Runs until the process is interrupted. Failed requests are logged and
+    /// do not stop the server.
+    pub(super) fn serve(&self) -> Result<(), ToolsError> {
+        let address = self.listener.local_addr()?;
+        let host = match address.ip() {
+            ip if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            ip => ip,
+        };
+        let url = match host {
+            IpAddr::V4(ip) => format!("http://{ip}:{}", address.port()),
+            IpAddr::V6(ip) => format!("http://[{ip}]:{}", address.port()),
+        };
+        console!("   Listening {url} ({})", self.repository.display());
+
+        let entries = self.entries()?;
+        for entry in &entries {
+            console!("   Extension {}", entry.path);
+            if !entry.compressed && !self.gzip {
+                console!("     Warning DuckDB requests {}.gz from HTTP repositories; pass --gzip or pack with --compress gzip", entry.path);
+            }
+        }
+        let names = entries.iter().map(|entry| entry.name.as_str()).collect::<BTreeSet<_>>();
+        if names.is_empty() {
+            console!("     Warning no extensions found in {}", self.repository.display());
+        }
+        console!("        Note start DuckDB with -unsigned to load extensions that are not signed");
+        println!("SET custom_extension_repository = '{url}';");
+        for name in names {
+            println!("INSTALL {name}; LOAD {name};");
+        }
+
+        for stream in self.listener.incoming() {
+            if let Err(error) = stream.and_then(|stream| self.respond(stream)) {
+                console!("     Warning {error}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the extension files in the repository layout
+    fn entries(&self) -> Result<Vec<RepositoryEntry>, ToolsError> {
+        let mut entries = Vec::new();
+        for version in self.repository.read_dir()? {
+            let version = version?.path();
+            if !version.is_dir() {
+                continue;
+            }
+            for platform in version.read_dir()? {
+                let platform = platform?.path();
+                if !platform.is_dir() {
+                    continue;
+                }
+                for file in platform.read_dir()? {
+                    let file = file?.path();
+                    let Some(file_name) = file.file_name().and_then(|name| name.to_str()) else {
+                        continue;
+                    };
+                    let (name, compressed) = match file_name.strip_suffix(".duckdb_extension.gz") {
+                        Some(name) => (name, true),
+                        None => match file_name.strip_suffix(".duckdb_extension") {
+                            Some(name) => (name, false),
+                            None => continue,
+                        },
+                    };
+                    let path = file.strip_prefix(&self.repository).unwrap_or(&file);
+                    entries.push(RepositoryEntry {
+                        path: path.to_string_lossy().replace('\\', "/"),
+                        name: name.to_owned(),
+                        compressed,
+                    });
+                }
+            }
+        }
+        entries.sort_by(|left, right| left.path.cmp(&right.path));
+        Ok(entries)
+    }
+
+    /// Answers a single HTTP request and logs it
+    fn respond(&self, mut stream: TcpStream) -> Result<(), std::io::Error> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut reader = BufReader::new(&stream);
+        let mut request = String::new();
+        reader.read_line(&mut request)?;
+        // Headers are not needed, but must be read before the connection is closed
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+
+        let mut parts = request.split_whitespace();
+        let method = parts.next().unwrap_or_default();
+        let target = parts.next().unwrap_or_default();
+        let path = target.split(['?', '#']).next().unwrap_or_default();
+        let (status, body) = match method {
+            "GET" | "HEAD" => match percent_decode(path).map(|path| (self.read(&path), path)) {
+                Some((Some(Ok(body)), _)) => ("200 OK", body),
+                Some((Some(Err(error)), path)) => {
+                    console!("     Warning cannot read {path}: {error}");
+                    ("500 Internal Server Error", b"internal server error\n".to_vec())
+                }
+                Some((None, _)) => ("404 Not Found", b"not found\n".to_vec()),
+                None => ("400 Bad Request", b"bad request\n".to_vec()),
+            },
+            _ => ("405 Method Not Allowed", b"method not allowed\n".to_vec()),
+        };
+        console!("     Serving {method} {path} ({status}, {} bytes)", body.len());
+
+        write!(
+            stream,
+            "HTTP/1.1 {status}\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )?;
+        if method != "HEAD" {
+            stream.write_all(&body)?;
+        }
+        stream.flush()
+    }
+
+    /// Reads the file a request path refers to
+    ///
+    /// With `--gzip`, a missing `.gz` file is compressed from the plain file.
+    /// Returns `None` for paths outside the repository or missing files.
+    fn read(&self, path: &str) -> Option<Result<Vec<u8>, std::io::Error>> {
+        let relative = Path::new(path.trim_start_matches('/'));
+        if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+            return None;
+        }
+        let file = self.repository.join(relative);
+        if file.is_file() {
+            return Some(std::fs::read(file));
+        }
+        let plain = path.strip_suffix(".gz").map(|plain| self.repository.join(plain.trim_start_matches('/')));
+        match plain {
+            Some(plain) if self.gzip && plain.is_file() => Some(std::fs::File::open(plain).and_then(|file| {
+                let mut compressed = Vec::new();
+                Compression::Gzip.compress(file, &mut compressed)?;
+                Ok(compressed)
+            })),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes `%XX` escapes in a request path
+///
+/// Returns `None` for malformed escapes, NUL bytes or paths that are not UTF-8.
+fn percent_decode(path: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail.get(..2).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok().filter(|path| !path.contains('\0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_request_paths() {
+        assert_eq!(percent_decode("/v1.4.2/linux_amd64/quack.duckdb_extension.gz").as_deref(), Some("/v1.4.2/linux_amd64/quack.duckdb_extension.gz"));
+        assert_eq!(percent_decode("/my%20repo/%E4%B8%AD.gz").as_deref(), Some("/my repo/中.gz"));
+        assert_eq!(percent_decode("/%2e%2e/secret").as_deref(), Some("/../secret"));
+        assert_eq!(percent_decode("/%zz"), None);
+        assert_eq!(percent_decode("/%+f"), None);
+        assert_eq!(percent_decode("/%2"), None);
+        assert_eq!(percent_decode("/%00"), None);
+        assert_eq!(percent_decode("/%ff"), None);
+    }
+}
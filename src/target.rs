@@ -0,0 +1,238 @@
+//! Resolution of the cargo build target
+//!
+//! The DuckDB platform of a build is derived from the target cargo compiles
+//! for. This module resolves that target with the same precedence as cargo,
+//! so that targets configured outside the command line are not mistaken for
+//! native builds.
+
+use crate::config::Source;
+use crate::config::Sourced;
+use crate::error::ToolsError;
+use cargo_metadata::camino::Utf8PathBuf;
+use std::env;
+use std::path::Path;
+use std::path::PathBuf;
+use toml::Table;
+use toml::Value;
+
+/// Environment variable overriding `build.target`
+const TARGET_ENV: &str = "CARGO_BUILD_TARGET";
+
+/// Resolves the targets `cargo build` will compile for
+///
+/// The sources are consulted in cargo's order of precedence:
+/// 1. `--target` arguments
+/// 2. `build.target` in `--config` overrides
+/// 3. The `CARGO_BUILD_TARGET` environment variable
+/// 4. `build.target` in the nearest `.cargo/config.toml` from the working
+///    directory up, then in `$CARGO_HOME/config.toml`
+///
+/// Paths to target specification files are made absolute. Each target
+/// keeps the place it was read from. Returns an empty list for native builds.
+pub(super) fn build_targets(args: &[String]) -> Result<Vec<Sourced<String>>, ToolsError> {
+    let working_directory = env::current_dir()?;
+    let targets = option_values(args, "--target");
+    if !targets.is_empty() {
+        return Ok(resolve_all(targets, &working_directory, Source::CargoOption("--target".to_owned())));
+    }
+
+    // Later `--config` values take precedence over earlier ones
+    for value in option_values(args, "--config").iter().rev() {
+        let path = working_directory.join(value);
+        let targets = if path.is_file() {
+            read_config(&path)?
+        } else {
+            let table = value.parse::<Table>().map_err(|error| ToolsError::InvalidCargoConfig {
+                path: format!("--config {value}"),
+                reason: error.message().to_owned(),
+            })?;
+            config_targets(&table, &working_directory, Source::CargoOption(format!("--config {value}")))
+        };
+        if let Some(targets) = targets {
+            return Ok(targets);
+        }
+    }
+
+    if let Ok(target) = env::var(TARGET_ENV)
+        && !target.is_empty()
+    {
+        return Ok(resolve_all(vec![target], &working_directory, Source::Environment(TARGET_ENV.to_owned())));
+    }
+
+    let cargo_home = env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::home_dir().map(|home| home.join(".cargo")));
+    let directories = working_directory
+        .ancestors()
+        .map(|directory| directory.join(".cargo"))
+        .chain(cargo_home);
+    for directory in directories {
+        // Cargo prefers the legacy extension-less file if both exist
+        let config = ["config", "config.toml"]
+            .iter()
+            .map(|name| directory.join(name))
+            .find(|path| path.is_file());
+        if let Some(config) = config
+            && let Some(targets) = read_config(&config)?
+        {
+            return Ok(targets);
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// Returns the `--target-dir` passed to `cargo build`, if any
+///
+/// Other ways of setting the target directory are already reflected in the
+/// output of `cargo metadata`.
+pub(super) fn target_directory(args: &[String]) -> Result<Option<Utf8PathBuf>, ToolsError> {
+    let Some(directory) = option_values(args, "--target-dir").pop() else {
+        return Ok(None);
+    };
+    let directory = env::current_dir()?.join(directory);
+    Ok(Utf8PathBuf::from_path_buf(directory).ok())
+}
+
+/// Returns the name cargo uses for a target in the target directory
+///
+/// Target specification files are named after their file stem.
+pub(super) fn target_name(target: &str) -> &str {
+    match target.strip_suffix(".json") {
+        Some(_) => Path::new(target)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(target),
+        None => target,
+    }
+}
+
+/// Returns the target triple a target builds for
+///
+/// For target specification files this is their `llvm-target`, falling
+/// back to the file stem if the file does not declare one.
+pub(super) fn target_triple(target: &str) -> Result<String, ToolsError> {
+    if !target.ends_with(".json") {
+        return Ok(target.to_owned());
+    }
+    let specification = std::fs::read_to_string(target)?;
+    let triple = serde_json::from_str::<serde_json::Value>(&specification)
+        .ok()
+        .and_then(|specification| specification.get("llvm-target")?.as_str().map(str::to_owned));
+    Ok(triple.unwrap_or_else(|| target_name(target).to_owned()))
+}
+
+/// Collects the values of a cargo option given as `--name value` or `--name=value`
+///
+/// Arguments after `--` are not cargo's and are ignored.
+fn option_values(args: &[String], name: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut arguments = args.iter().take_while(|argument| *argument != "--");
+    while let Some(argument) = arguments.next() {
+        if argument == name {
+            values.extend(arguments.next().cloned());
+        } else if let Some(value) = argument.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+            values.push(value.to_owned());
+        }
+    }
+    values
+}
+
+/// Reads `build.target` from a cargo configuration file
+///
+/// Relative paths are resolved against the parent of the directory holding
+/// the file, as cargo does for `.cargo/config.toml`.
+fn read_config(path: &Path) -> Result<Option<Vec<Sourced<String>>>, ToolsError> {
+    let invalid = |reason: String| ToolsError::InvalidCargoConfig {
+        path: path.display().to_string(),
+        reason,
+    };
+    let content = std::fs::read_to_string(path).map_err(|error| invalid(error.to_string()))?;
+    let table = content
+        .parse::<Table>()
+        .map_err(|error| invalid(error.message().to_owned()))?;
+    let base = path.parent().and_then(Path::parent).unwrap_or(Path::new("."));
+    Ok(config_targets(&table, base, Source::CargoConfig(path.display().to_string())))
+}
+
+/// Extracts `build.target`, which may be a single target or a list
+fn config_targets(table: &Table, base: &Path, source: Source) -> Option<Vec<Sourced<String>>> {
+    let targets = match table.get("build")?.get("target")? {
+        Value::String(target) => vec![target.to_owned()],
+        Value::Array(targets) => targets
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_owned)
+            .collect(),
+        _ => return None,
+    };
+    Some(resolve_all(targets, base, source))
+}
+
+/// Makes paths to target specification files absolute and attaches their source
+fn resolve_all(targets: Vec<String>, base: &Path, source: Source) -> Vec<Sourced<String>> {
+    targets
+        .into_iter()
+        .map(|target| match target.ends_with(".json") {
+            true => base.join(&target).display().to_string(),
+            false => target,
+        })
+        .map(|target| Sourced::new(target, source.to_owned()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn separates_target_from_target_dir() {
+        let args = args(&["--target-dir", "out", "--target", "a", "--target-dir=out2", "--target=b"]);
+        assert_eq!(option_values(&args, "--target"), ["a", "b"]);
+        assert_eq!(option_values(&args, "--target-dir"), ["out", "out2"]);
+    }
+
+    #[test]
+    fn ignores_arguments_after_separator() {
+        let args = args(&["--target=a", "--", "--target", "b", "--target=c"]);
+        assert_eq!(option_values(&args, "--target"), ["a"]);
+    }
+
+    #[test]
+    fn prefers_target_arguments() {
+        let targets = build_targets(&args(&["--release", "--target=wasm32-unknown-emscripten"])).unwrap();
+        let source = Source::CargoOption("--target".to_owned());
+        assert_eq!(targets, [Sourced::new("wasm32-unknown-emscripten".to_owned(), source)]);
+    }
+
+    #[test]
+    fn reads_target_lists_relative_to_config() {
+        let root = std::env::temp_dir().join(format!("quack-target-{}", std::process::id()));
+        std::fs::create_dir_all(root.join(".cargo")).unwrap();
+        let config = root.join(".cargo/config.toml");
+        std::fs::write(&config, "[build]\ntarget = [\"x86_64-unknown-linux-gnu\", \"specs/quack.json\"]\n").unwrap();
+        let targets = read_config(&config);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let source = Source::CargoConfig(config.display().to_string());
+        let expected = [
+            Sourced::new("x86_64-unknown-linux-gnu".to_owned(), source.to_owned()),
+            Sourced::new(root.join("specs/quack.json").display().to_string(), source),
+        ];
+        assert_eq!(targets.unwrap().unwrap(), expected);
+    }
+
+    #[test]
+    fn reads_single_target_from_config_overrides() {
+        let table = "build.target = \"specs/quack.json\"".parse::<Table>().unwrap();
+        let source = Source::CargoOption("--config".to_owned());
+        let targets = config_targets(&table, Path::new("/work"), source.to_owned()).unwrap();
+        assert_eq!(targets, [Sourced::new(Path::new("/work/specs/quack.json").display().to_string(), source)]);
+        assert_eq!(target_name(&targets[0].value), "quack");
+        let table = "[build]\njobs = 4".parse::<Table>().unwrap();
+        assert_eq!(config_targets(&table, Path::new("/work"), Source::Default), None);
+    }
+}
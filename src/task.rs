@@ -1,26 +1,51 @@
 //! Task dispatching and command line parsing for cargo-duckdb-ext-tools
 //!
 //! This module handles the routing of command line invocations to the appropriate
-//! subcommands (`duckdb-ext-build` and `duckdb-ext-pack`), parsing arguments
+//! subcommands (`duckdb-ext-build`, `duckdb-ext-pack`, `duckdb-ext-inspect`,
+//! `duckdb-ext-unpack`, `duckdb-ext-retag`, `duckdb-ext-config` and
+//! `duckdb-ext-serve`), parsing arguments
 //! and executing the corresponding operations.
 
 use crate::builder::Builder;
 use crate::builder::BuilderOptions;
+use crate::configurator::Configurator;
+use crate::configurator::ConfiguratorOptions;
+use crate::console;
 use crate::error::ToolsError;
+use crate::inspector::Inspector;
+use crate::inspector::InspectorOptions;
+use crate::logger::QUITE;
 use crate::packer::Packer;
 use crate::packer::PackerOptions;
+use crate::retagger::Retagger;
+use crate::retagger::RetaggerOptions;
+use crate::server::Server;
+use crate::server::ServerOptions;
+use crate::unpacker::Unpacker;
+use crate::unpacker::UnpackerOptions;
+use clap::CommandFactory;
 use clap::Parser;
 use std::env::args;
 
 /// Represents the different tasks that can be executed
 ///
-/// This enum distinguishes between the two main operations:
+/// This enum distinguishes between the main operations:
 /// - Build: Combines compilation and packaging
 /// - Pack: Only appends metadata to existing libraries
+/// - Inspect: Prints the metadata of existing extensions
+/// - Unpack: Strips the metadata from existing extensions
+/// - Retag: Rewrites metadata fields of existing extensions in place
+/// - Config: Prints the effective build settings and their sources
+/// - Serve: Serves an extension repository over HTTP
 #[derive(Debug)]
 pub(crate) enum Task {
     Build(Vec<String>),
     Pack(Vec<String>),
+    Inspect(Vec<String>),
+    Unpack(Vec<String>),
+    Retag(Vec<String>),
+    Config(Vec<String>),
+    Serve(Vec<String>),
 }
 
 impl Task {
@@ -28,7 +53,8 @@ impl Task {
     ///
     /// This method analyzes the command line to determine which subcommand
     /// was invoked and extracts the relevant arguments for that command.
-    pub(crate) fn new() -> Task {
+    /// Fails if the program name does not match any subcommand.
+    pub(crate) fn new() -> Result<Task, ToolsError> {
         let mut iterator = args().peekable();
         let mut arguments = Vec::new();
         while let Some(program) = iterator.next() {
@@ -40,7 +66,7 @@ impl Task {
             }
         }
         arguments.extend(iterator);
-        if let Some(program) = arguments.get(0) {
+        if let Some(program) = arguments.first() {
             let program_base = std::path::Path::new(program)
                 .file_name()
                 .and_then(|name| name.to_str())
@@ -49,30 +75,75 @@ impl Task {
                 .unwrap_or(program);
                 
             if program_base.ends_with("duckdb-ext-build") {
-                return Task::Build(arguments);
+                return Ok(Task::Build(arguments));
             }
             if program_base.ends_with("duckdb-ext-pack") {
-                return Task::Pack(arguments);
+                return Ok(Task::Pack(arguments));
+            }
+            if program_base.ends_with("duckdb-ext-inspect") {
+                return Ok(Task::Inspect(arguments));
+            }
+            if program_base.ends_with("duckdb-ext-unpack") {
+                return Ok(Task::Unpack(arguments));
+            }
+            if program_base.ends_with("duckdb-ext-retag") {
+                return Ok(Task::Retag(arguments));
+            }
+            if program_base.ends_with("duckdb-ext-config") {
+                return Ok(Task::Config(arguments));
+            }
+            if program_base.ends_with("duckdb-ext-serve") {
+                return Ok(Task::Serve(arguments));
             }
         }
-        panic!("Unsupported task: {arguments:?}");
+        Err(ToolsError::UnsupportedTask(arguments.first().cloned().unwrap_or_default()))
     }
 
     /// Executes the selected task
     ///
-    /// For Build tasks: parses options, builds the project, and packages extensions
+    /// For Build tasks: parses options, builds the project, packages extensions
+    /// and prints a summary of the packaged extensions
     /// For Pack tasks: parses options and appends metadata to existing libraries
+    /// For Inspect tasks: parses options and prints the footer of an existing extension
+    /// For Unpack tasks: parses options and recovers the library from an existing extension
+    /// For Retag tasks: parses options and rewrites footer fields of an existing extension
+    /// For Config tasks: parses options and prints the settings a build would use
+    /// For Serve tasks: parses options and serves an extension repository until interrupted
     pub(crate) fn execute(&self) -> Result<(), ToolsError> {
         if let Task::Build(args) = self {
-            let options = BuilderOptions::parse_from(args);
+            let options = BuilderOptions::from_matches(&BuilderOptions::command().get_matches_from(args));
             let mut builder = Builder::try_from(options)?;
-            for mut packer in builder.build()? {
+            let mut packers = builder.build()?;
+            for packer in packers.iter_mut() {
                 packer.write_metadata()?;
             }
+            let noun = if packers.len() == 1 { "extension" } else { "extensions" };
+            console!("    Packaged {} DuckDB {noun}", packers.len());
+            for packer in &packers {
+                console!(
+                    "             {} ({}, {}, DuckDB {})",
+                    packer.extension_path, packer.extension_version, packer.duckdb_platform, packer.duckdb_version
+                );
+            }
         } else if let Task::Pack(args) = self {
             let options = PackerOptions::parse_from(args);
             let mut packer = Packer::try_from(options)?;
             packer.write_metadata()?;
+        } else if let Task::Inspect(args) = self {
+            let options = InspectorOptions::parse_from(args);
+            Inspector::from(options).inspect()?;
+        } else if let Task::Unpack(args) = self {
+            let options = UnpackerOptions::parse_from(args);
+            Unpacker::try_from(options)?.unpack()?;
+        } else if let Task::Retag(args) = self {
+            let options = RetaggerOptions::parse_from(args);
+            Retagger::try_from(options)?.retag()?;
+        } else if let Task::Config(args) = self {
+            let options = ConfiguratorOptions::from_matches(&ConfiguratorOptions::command().get_matches_from(args));
+            Configurator::try_from(options)?.show()?;
+        } else if let Task::Serve(args) = self {
+            let options = ServerOptions::parse_from(args);
+            Server::try_from(options)?.serve()?;
         }
         Ok(())
     }
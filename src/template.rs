@@ -0,0 +1,98 @@
+//! Extension path templates
+//!
+//! Extension paths may contain placeholders such as `{platform}` that are
+//! replaced for every packed extension, so that the outputs of several
+//! packages and build targets can be collected in one directory under
+//! unique names.
+
+use crate::error::ToolsError;
+
+/// Placeholders accepted in extension path templates
+pub(super) const PLACEHOLDERS: [&str; 4] = ["name", "version", "duckdb_version", "platform"];
+
+/// Returns `true` if the path contains placeholders
+pub(super) fn is_template(path: &str) -> bool {
+    path.contains('{')
+}
+
+/// Checks that a template only uses known placeholders
+pub(super) fn check_template(template: &str) -> Result<(), ToolsError> {
+    render_template(template, |_| Some(""))?;
+    Ok(())
+}
+
+/// Replaces every placeholder of a template
+///
+/// `value` returns the replacement of a placeholder name, or `None` if the
+/// name is unknown.
+pub(super) fn render_template<'a>(template: &str, value: impl Fn(&str) -> Option<&'a str>) -> Result<String, ToolsError> {
+    let unknown = |placeholder: &str| ToolsError::UnknownPlaceholder {
+        template: template.to_owned(),
+        placeholder: placeholder.to_owned(),
+    };
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let Some(length) = rest[start..].find('}') else {
+            return Err(unknown(&rest[start..]));
+        };
+        let placeholder = &rest[start + 1..start + length];
+        let replacement = PLACEHOLDERS
+            .contains(&placeholder)
+            .then(|| value(placeholder))
+            .flatten()
+            .ok_or_else(|| unknown(&rest[start..=start + length]))?;
+        rendered.push_str(replacement);
+        rest = &rest[start + length + 1..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str) -> Result<String, ToolsError> {
+        render_template(template, |placeholder| match placeholder {
+            "name" => Some("quack"),
+            "version" => Some("v0.1.0"),
+            "duckdb_version" => Some("v1.2.0"),
+            "platform" => Some("linux_amd64"),
+            _ => None,
+        })
+    }
+
+    fn unknown_placeholder(template: &str) -> String {
+        match render(template) {
+            Err(ToolsError::UnknownPlaceholder { template: reported, placeholder }) => {
+                assert_eq!(reported, template);
+                placeholder
+            }
+            result => panic!("{template} rendered to {result:?}"),
+        }
+    }
+
+    #[test]
+    fn renders_all_placeholders() {
+        let template = "dist/{duckdb_version}/{platform}/{name}-{version}.duckdb_extension";
+        assert!(is_template(template));
+        assert!(check_template(template).is_ok());
+        assert_eq!(render(template).unwrap(), "dist/v1.2.0/linux_amd64/quack-v0.1.0.duckdb_extension");
+    }
+
+    #[test]
+    fn keeps_plain_paths() {
+        assert!(!is_template("dist/quack.duckdb_extension"));
+        assert_eq!(render("dist/quack.duckdb_extension").unwrap(), "dist/quack.duckdb_extension");
+    }
+
+    #[test]
+    fn rejects_malformed_placeholders() {
+        assert_eq!(unknown_placeholder("{name}-{foo}.duckdb_extension"), "{foo}");
+        assert_eq!(unknown_placeholder("{name.duckdb_extension"), "{name.duckdb_extension");
+        assert_eq!(unknown_placeholder("{}.duckdb_extension"), "{}");
+        assert!(check_template("{foo}").is_err());
+    }
+}
@@ -0,0 +1,117 @@
+//! DuckDB extension unpacker
+//!
+//! This module implements the `duckdb-ext-unpack` subcommand, which validates
+//! the metadata footer of an extension file and recovers the original dynamic
+//! library by dropping the trailing 534 bytes.
+
+use crate::console;
+use crate::error::ToolsError;
+use crate::fs::copy_without_footer;
+use crate::fs::is_same_file;
+use crate::inspector::metadata_json;
+use crate::inspector::read_metadata;
+use crate::logger::QUITE;
+use crate::logger::set_quiet;
+use clap::Parser;
+use std::path::Path;
+
+/// Command line options for the `duckdb-ext-unpack` subcommand
+///
+/// This struct defines the parameters required to strip the metadata
+/// footer from an existing DuckDB extension file.
+#[derive(Parser, Debug)]
+#[command(name = "duckdb-ext-unpack", version, author, about = "", long_about = "")]
+pub(super) struct UnpackerOptions {
+    /// Path to the input extension file
+    #[arg(short = 'i', long, value_name = "EXTENSION-PATH")]
+    extension_path: String,
+
+    /// Path where the dynamic library should be written (derived from the platform if not specified)
+    #[arg(short = 'o', long, value_name = "LIBRARY-PATH")]
+    library_path: Option<String>,
+
+    /// Also write the decoded metadata as JSON next to the library
+    #[arg(short = 'j', long, default_value_t = false)]
+    metadata_json: bool,
+
+    /// Suppress console output
+    #[arg(short = 'q', long, default_value_t = false)]
+    quiet: bool,
+}
+
+/// Recovers the raw dynamic library from a DuckDB extension
+pub(super) struct Unpacker {
+    /// Path of the input extension file
+    extension_path: String,
+    /// Optional override for the output library path
+    library_path: Option<String>,
+    /// Whether to write the decoded metadata as JSON
+    metadata_json: bool,
+}
+
+impl TryFrom<UnpackerOptions> for Unpacker {
+    type Error = ToolsError;
+
+    /// Constructs an Unpacker from command line options
+    ///
+    /// This conversion sets up the global quiet flag.
+    fn try_from(parameters: UnpackerOptions) -> Result<Self, Self::Error> {
+        set_quiet(parameters.quiet)?;
+        Ok(Self {
+            extension_path: parameters.extension_path,
+            library_path: parameters.library_path,
+            metadata_json: parameters.metadata_json,
+        })
+    }
+}
+
+impl Unpacker {
+    /// Validates the footer and writes the library without it
+    ///
+    /// When no output path is given, the library is written next to the
+    /// extension with the file extension expected on the packed platform,
+    /// unless a file already exists there. The extension itself is never
+    /// overwritten.
+    pub(super) fn unpack(&self) -> Result<(), ToolsError> {
+        let metadata = read_metadata(&self.extension_path)?;
+        let library_path = match self.library_path.to_owned() {
+            Some(library_path) => library_path,
+            None => {
+                let library_path = Self::default_library_path(&self.extension_path, &metadata.duckdb_platform);
+                if Path::new(&library_path).exists() && !is_same_file(&self.extension_path, &library_path) {
+                    return Err(ToolsError::LibraryExists(library_path));
+                }
+                library_path
+            }
+        };
+        if is_same_file(&self.extension_path, &library_path) {
+            return Err(ToolsError::OutputIsInput(library_path));
+        }
+        copy_without_footer(&self.extension_path, &library_path)?;
+
+        if self.metadata_json {
+            let json_path = Path::new(&library_path).with_extension("json");
+            console!("     Writing Metadata File ({})", json_path.display());
+            std::fs::write(&json_path, format!("{:#}\n", metadata_json(&metadata)))?;
+        }
+        console!("    Finished Dynamic Library");
+        Ok(())
+    }
+
+    /// Derives the library path from the extension path and packed platform
+    fn default_library_path(extension_path: &str, duckdb_platform: &str) -> String {
+        let extension = if duckdb_platform.starts_with("osx") {
+            "dylib"
+        } else if duckdb_platform.starts_with("windows") {
+            "dll"
+        } else if duckdb_platform.starts_with("wasm") {
+            "wasm"
+        } else {
+            "so"
+        };
+        Path::new(extension_path)
+            .with_extension(extension)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
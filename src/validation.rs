@@ -0,0 +1,237 @@
+//! Validation of extension metadata values
+//!
+//! This module checks every value that ends up in the metadata footer before
+//! any file is copied, so that problems are reported all at once with hints
+//! instead of surfacing as a half-written extension file.
+
+use crate::abi::AbiType;
+use crate::abi::MIN_C_API_VERSION;
+use crate::console;
+use crate::error::ToolsError;
+use crate::logger::QUITE;
+use crate::platform::Platform;
+use cargo_duckdb_ext_tools::FIELD_SIZE;
+use cargo_metadata::semver::Version;
+use thiserror::Error;
+
+/// A single problem found in a metadata value
+#[derive(Error, Debug)]
+pub(super) enum ValidationError {
+    /// The value does not fit into a 32-byte footer field
+    #[error("{field} `{value}` is {} bytes long, but footer fields hold at most {FIELD_SIZE} bytes", value.len())]
+    TooLong { field: &'static str, value: String },
+
+    /// The value contains a NUL, whitespace, control or non-ASCII character
+    #[error("{field} `{}` contains the invalid character {character:?}; only printable ASCII is allowed", value.escape_debug())]
+    InvalidCharacter {
+        field: &'static str,
+        value: String,
+        character: char,
+    },
+
+    /// The value is not a `v`-prefixed semantic version
+    #[error("{field} `{value}` is not a `v`-prefixed semantic version (e.g. \"v1.0.0\")")]
+    InvalidVersion { field: &'static str, value: String },
+
+    /// The DuckDB version does not follow the rules of the ABI type
+    #[error("DuckDB version `{0}` is not a C API version; C_STRUCT extensions record the minimum C API version (e.g. \"v{MIN_C_API_VERSION}\") rather than a DuckDB release")]
+    NotCApiVersion(String),
+
+    /// The platform is neither known nor shaped like a DuckDB platform
+    #[error("DuckDB platform `{0}` is not of the form `<os>_<arch>[_<variant>]`; expected one of {known}", known = Platform::known_names().collect::<Vec<_>>().join(", "))]
+    MalformedPlatform(String),
+}
+
+/// Collects validation problems across all metadata values
+///
+/// Each check records its problems instead of returning early, so that
+/// `finish` can report everything that is wrong in a single error.
+#[derive(Default)]
+pub(super) struct Validator {
+    errors: Vec<ValidationError>,
+}
+
+impl Validator {
+    /// Checks an extension version
+    pub(super) fn extension_version(mut self, value: Option<&str>) -> Self {
+        if let Some(value) = value {
+            self.version("extension version", value);
+        }
+        self
+    }
+
+    /// Checks a DuckDB version against the rules of the ABI type
+    pub(super) fn duckdb_version(mut self, abi_type: AbiType, value: Option<&str>) -> Self {
+        if let Some(value) = value
+            && let Some(version) = self.version("DuckDB version", value)
+            && !abi_type.accepts_duckdb_version(&version)
+        {
+            self.errors.push(ValidationError::NotCApiVersion(value.to_owned()));
+        }
+        self
+    }
+
+    /// Checks the DuckDB release a C_STRUCT extension is built for
+    ///
+    /// Unlike the footer's DuckDB version, any release is accepted.
+    pub(super) fn duckdb_release(mut self, value: Option<&str>) -> Self {
+        if let Some(value) = value {
+            self.version("DuckDB version", value);
+        }
+        self
+    }
+
+    /// Checks a platform identifier
+    ///
+    /// Identifiers outside the known platform table are accepted with a
+    /// warning as long as they look like a DuckDB platform.
+    pub(super) fn duckdb_platform(mut self, value: Option<&Platform>) -> Self {
+        if let Some(platform) = value
+            && self.field("DuckDB platform", platform.as_str())
+            && platform.is_other()
+        {
+            if platform.is_well_formed() {
+                console!("     Warning DuckDB platform `{platform}` is not a known platform, packing it verbatim");
+            } else {
+                self.errors.push(ValidationError::MalformedPlatform(platform.to_string()));
+            }
+        }
+        self
+    }
+
+    /// Returns all collected problems as a single error
+    pub(super) fn finish(self) -> Result<(), ToolsError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ToolsError::InvalidMetadata(self.errors))
+        }
+    }
+
+    /// Checks that a value is a `v`-prefixed semantic version
+    ///
+    /// Returns the parsed version if no problem was recorded.
+    fn version(&mut self, field: &'static str, value: &str) -> Option<Version> {
+        if !self.field(field, value) {
+            return None;
+        }
+        let version = value.strip_prefix('v').and_then(|version| Version::parse(version).ok());
+        if version.is_none() {
+            self.errors.push(ValidationError::InvalidVersion {
+                field,
+                value: value.to_owned(),
+            });
+        }
+        version
+    }
+
+    /// Checks the length and charset shared by all fields
+    ///
+    /// Returns `false` if a problem was recorded, so that more specific
+    /// checks are skipped for values that are already known to be invalid.
+    fn field(&mut self, field: &'static str, value: &str) -> bool {
+        if let Some(character) = value.chars().find(|character| !character.is_ascii_graphic()) {
+            self.errors.push(ValidationError::InvalidCharacter {
+                field,
+                value: value.to_owned(),
+                character,
+            });
+            false
+        } else if value.len() > FIELD_SIZE {
+            self.errors.push(ValidationError::TooLong {
+                field,
+                value: value.to_owned(),
+            });
+            false
+        } else {
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(validator: Validator) -> Vec<ValidationError> {
+        match validator.finish() {
+            Ok(()) => Vec::new(),
+            Err(ToolsError::InvalidMetadata(errors)) => errors,
+            Err(error) => panic!("unexpected error {error}"),
+        }
+    }
+
+    #[test]
+    fn accepts_valid_values() {
+        let validator = Validator::default()
+            .extension_version(Some("v0.3.1"))
+            .duckdb_version(AbiType::CStructUnstable, Some("v1.4.2"))
+            .duckdb_version(AbiType::CStruct, Some("v1.2.0"))
+            .duckdb_platform(Some(&Platform::LinuxAmd64))
+            .duckdb_platform(None);
+        assert!(errors(validator).is_empty());
+    }
+
+    #[test]
+    fn collects_every_problem() {
+        let errors = errors(
+            Validator::default()
+                .extension_version(Some("1.0.0"))
+                .duckdb_version(AbiType::CStruct, Some("v1.4.2"))
+                .duckdb_platform(Some(&Platform::from("Linux-AMD64"))),
+        );
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(&errors[0], ValidationError::InvalidVersion { field: "extension version", value } if value == "1.0.0"));
+        assert!(matches!(&errors[1], ValidationError::NotCApiVersion(value) if value == "v1.4.2"));
+        assert!(matches!(&errors[2], ValidationError::MalformedPlatform(value) if value == "Linux-AMD64"));
+    }
+
+    #[test]
+    fn rejects_values_longer_than_a_field() {
+        let version = format!("v1.0.0-{}", "a".repeat(FIELD_SIZE));
+        let problems = errors(Validator::default().extension_version(Some(&version)));
+        assert!(matches!(&problems[..], [ValidationError::TooLong { field: "extension version", .. }]));
+        let exact = format!("v1.0.0-{}", "a".repeat(FIELD_SIZE - 7));
+        assert!(errors(Validator::default().extension_version(Some(&exact))).is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_characters() {
+        for (value, invalid) in [("v1.0.0\0", '\0'), ("v1.0.0 ", ' '), ("v1.0.0-é", 'é')] {
+            let errors = errors(Validator::default().extension_version(Some(value)));
+            assert!(
+                matches!(&errors[..], [ValidationError::InvalidCharacter { character, .. }] if *character == invalid),
+                "{value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn requires_v_prefixed_versions() {
+        for value in ["1.4.2", "v1.4", "latest"] {
+            let errors = errors(Validator::default().duckdb_version(AbiType::Cpp, Some(value)));
+            assert!(matches!(&errors[..], [ValidationError::InvalidVersion { field: "DuckDB version", .. }]), "{value}");
+        }
+    }
+
+    #[test]
+    fn c_struct_requires_a_c_api_version() {
+        for value in ["v1.2.0", "v1.3.0", "v2.0.0"] {
+            assert!(errors(Validator::default().duckdb_version(AbiType::CStruct, Some(value))).is_empty(), "{value}");
+        }
+        for value in ["v1.1.0", "v1.2.1", "v1.3.0-dev", "v0.10.0"] {
+            let errors = errors(Validator::default().duckdb_version(AbiType::CStruct, Some(value)));
+            assert!(matches!(&errors[..], [ValidationError::NotCApiVersion(_)]), "{value}");
+        }
+        assert!(errors(Validator::default().duckdb_version(AbiType::CStructUnstable, Some("v1.2.1"))).is_empty());
+    }
+
+    #[test]
+    fn unknown_platforms_only_warn_when_well_formed() {
+        assert!(errors(Validator::default().duckdb_platform(Some(&Platform::from("linux_riscv64")))).is_empty());
+        for value in ["linux", "linux__amd64", "Linux_amd64", "linux-amd64"] {
+            let errors = errors(Validator::default().duckdb_platform(Some(&Platform::from(value))));
+            assert!(matches!(&errors[..], [ValidationError::MalformedPlatform(_)]), "{value}");
+        }
+    }
+}